@@ -0,0 +1,196 @@
+use std::{collections::HashSet, path::Path};
+
+use crate::{
+    errors::{Error, IOError},
+    parser::{
+        ast::{
+            self,
+            visitor::{self, Visitor},
+            ExpressionType,
+        },
+        Parser,
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DocFormat {
+    Markdown,
+    Html,
+}
+
+/// A top level declaration of a script
+#[derive(Debug, Clone)]
+pub struct Item {
+    pub name: String,
+    /// Parameters of a function, `None` for variables
+    pub parameters: Option<Vec<String>>,
+    pub doc: Option<String>,
+}
+
+impl Item {
+    /// `add(a, b)` for functions, the name for variables
+    pub fn signature(&self) -> String {
+        match &self.parameters {
+            Some(parameters) => format!("{}({})", self.name, parameters.join(", ")),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// The declarations of one file
+#[derive(Debug, Clone)]
+pub struct Module {
+    pub path: String,
+    pub items: Vec<Item>,
+}
+
+/// Generates the API docs of the script and every file it includes
+pub fn generate(path: &str, source: &str, format: DocFormat) -> Result<String, Error> {
+    let modules = collect(path, source)?;
+
+    Ok(match format {
+        DocFormat::Markdown => markdown(&modules),
+        DocFormat::Html => html(&modules),
+    })
+}
+
+/// Reads the declarations of the script, followed by those of the files it
+/// includes with a literal path. Every file is read once, however often it is
+/// included. All functions are listed, variables only when they have docs.
+pub fn collect(path: &str, source: &str) -> Result<Vec<Module>, Error> {
+    let mut modules = Vec::new();
+    let mut seen = HashSet::new();
+    seen.insert(canonical(path));
+
+    let mut pending = vec![(path.to_string(), source.to_string())];
+    while let Some((path, source)) = pending.pop() {
+        let program = Parser::produce_ast(&source)?;
+
+        let mut includes = Includes::default();
+        includes.visit_program(&program);
+
+        // Read in reverse so the files are documented in the order they are included
+        for (include, line, column) in includes.paths.into_iter().rev() {
+            if !seen.insert(canonical(&include)) {
+                continue;
+            }
+
+            let source = std::fs::read_to_string(&include)
+                .map_err(|e| IOError::new(e, line, column).into())?;
+            pending.push((include, source));
+        }
+
+        modules.push(Module {
+            path,
+            items: program.body.iter().filter_map(item).collect(),
+        });
+    }
+
+    Ok(modules)
+}
+
+pub fn markdown(modules: &[Module]) -> String {
+    let mut output = String::new();
+
+    for module in modules {
+        if !output.is_empty() {
+            output.push('\n');
+        }
+        output.push_str(&format!("# {}\n", module.path));
+
+        for item in &module.items {
+            output.push_str(&format!("\n## `{}`\n", item.signature()));
+            if let Some(doc) = &item.doc {
+                output.push_str(&format!("\n{}\n", doc));
+            }
+        }
+    }
+
+    output
+}
+
+/// A standalone page, the docs are split into paragraphs at blank lines
+pub fn html(modules: &[Module]) -> String {
+    let title = match modules.first() {
+        Some(module) => escape(&module.path),
+        None => String::new(),
+    };
+
+    let mut output = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n",
+        title
+    );
+
+    for module in modules {
+        output.push_str(&format!("<h1>{}</h1>\n", escape(&module.path)));
+
+        for item in &module.items {
+            output.push_str(&format!(
+                "<h2><code>{}</code></h2>\n",
+                escape(&item.signature())
+            ));
+
+            let paragraphs = item.doc.iter().flat_map(|doc| doc.split("\n\n"));
+            for paragraph in paragraphs.filter(|paragraph| !paragraph.trim().is_empty()) {
+                output.push_str(&format!("<p>{}</p>\n", escape(paragraph.trim())));
+            }
+        }
+    }
+
+    output.push_str("</body>\n</html>\n");
+    output
+}
+
+fn item(expression: &ast::Expression) -> Option<Item> {
+    match &expression.expression_type {
+        ExpressionType::Function(function) => Some(Item {
+            name: identifier(&function.name)?,
+            parameters: Some(function.parameters.iter().filter_map(identifier).collect()),
+            doc: function.doc.clone(),
+        }),
+        ExpressionType::Assignment(assignment) if assignment.doc.is_some() => Some(Item {
+            name: identifier(&assignment.left)?,
+            parameters: None,
+            doc: assignment.doc.clone(),
+        }),
+        _ => None,
+    }
+}
+
+fn identifier(expression: &ast::Expression) -> Option<String> {
+    match &expression.expression_type {
+        ExpressionType::Identifier(identifier) => Some(identifier.name.to_string()),
+        _ => None,
+    }
+}
+
+/// Includes resolve relative to the working directory, so the same path is the same file
+fn canonical(path: &str) -> String {
+    match std::fs::canonicalize(Path::new(path)) {
+        Ok(path) => path.to_string_lossy().into_owned(),
+        Err(_) => path.to_string(),
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// The literal paths of the includes anywhere in a program, with their positions
+#[derive(Default)]
+struct Includes {
+    paths: Vec<(String, usize, usize)>,
+}
+
+impl Visitor for Includes {
+    fn visit_include(&mut self, include: &ast::Include) {
+        if let ExpressionType::String(path) = &include.path.expression_type {
+            self.paths
+                .push((path.clone(), include.path.line, include.path.column));
+        }
+        visitor::walk_include(self, include);
+    }
+}
@@ -0,0 +1,42 @@
+use std::fmt::Display;
+
+#[derive(Debug)]
+pub struct CharacterOutOfRange {
+    pub value: f64,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl CharacterOutOfRange {
+    pub fn new(value: f64, line: usize, column: usize) -> Self {
+        Self {
+            value,
+            line,
+            column,
+        }
+    }
+}
+
+impl Display for CharacterOutOfRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Character out of range: {}", self.value)
+    }
+}
+
+impl std::error::Error for CharacterOutOfRange {}
+
+impl Into<crate::errors::Error> for CharacterOutOfRange {
+    fn into(self) -> crate::errors::Error {
+        let message = format!("Character out of range: {}", self.value);
+        let line = self.line;
+        let column = self.column;
+
+        crate::errors::Error {
+            source: Box::new(self),
+            code: "CharacterOutOfRange",
+            line,
+            column,
+            message,
+        }
+    }
+}
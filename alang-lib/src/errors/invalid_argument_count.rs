@@ -45,6 +45,7 @@ impl Into<crate::errors::Error> for InvalidArgumentCount {
 
         crate::errors::Error {
             source: Box::new(self),
+            code: "InvalidArgumentCount",
             line: line,
             column: column,
             message,
@@ -0,0 +1,48 @@
+use std::fmt::Display;
+
+use crate::types::RuntimeType;
+
+#[derive(Debug)]
+pub struct InvalidArgumentType {
+    pub name: String,
+    pub argument: RuntimeType,
+    pub expected: String,
+}
+
+impl InvalidArgumentType {
+    pub fn new(name: &str, argument: RuntimeType, expected: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            argument,
+            expected: expected.to_string(),
+        }
+    }
+}
+
+impl Display for InvalidArgumentType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Invalid argument for function '{}', got {:?} expected {}",
+            self.name, self.argument.value, self.expected
+        )
+    }
+}
+
+impl std::error::Error for InvalidArgumentType {}
+
+impl Into<crate::errors::Error> for InvalidArgumentType {
+    fn into(self) -> crate::errors::Error {
+        let message = self.to_string();
+        let line = self.argument.line;
+        let column = self.argument.column;
+
+        crate::errors::Error {
+            source: Box::new(self),
+            code: "InvalidArgumentType",
+            line,
+            column,
+            message,
+        }
+    }
+}
@@ -28,6 +28,7 @@ impl Into<crate::errors::Error> for InvalidAssignment {
 
         crate::errors::Error {
             source: Box::new(self),
+            code: "InvalidAssignment",
             line: line,
             column: column,
             message,
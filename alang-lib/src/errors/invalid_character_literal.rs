@@ -28,6 +28,7 @@ impl Into<crate::errors::Error> for InvalidCharacterLiteral {
 
         crate::errors::Error {
             source: Box::new(self),
+            code: "InvalidCharacterLiteral",
             line: line,
             column: column,
             message,
@@ -35,6 +35,7 @@ impl Into<crate::errors::Error> for InvalidCondition {
 
         crate::errors::Error {
             source: Box::new(self),
+            code: "InvalidCondition",
             line: line,
             column: column,
             message,
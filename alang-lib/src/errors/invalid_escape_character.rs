@@ -33,6 +33,7 @@ impl Into<crate::errors::Error> for InvalidEscapeCharacter {
 
         crate::errors::Error {
             source: Box::new(self),
+            code: "InvalidEscapeCharacter",
             line: line,
             column: column,
             message,
@@ -0,0 +1,42 @@
+use std::fmt::Display;
+
+#[derive(Debug)]
+pub struct InvalidEscapeSequence {
+    pub sequence: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl InvalidEscapeSequence {
+    pub fn new(sequence: String, line: usize, column: usize) -> Self {
+        Self {
+            sequence,
+            line,
+            column,
+        }
+    }
+}
+
+impl Display for InvalidEscapeSequence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid escape sequence: '{}'", self.sequence)
+    }
+}
+
+impl std::error::Error for InvalidEscapeSequence {}
+
+impl Into<crate::errors::Error> for InvalidEscapeSequence {
+    fn into(self) -> crate::errors::Error {
+        let message = format!("Invalid escape sequence: '{}'", self.sequence);
+        let line = self.line;
+        let column = self.column;
+
+        crate::errors::Error {
+            source: Box::new(self),
+            code: "InvalidEscapeSequence",
+            line,
+            column,
+            message,
+        }
+    }
+}
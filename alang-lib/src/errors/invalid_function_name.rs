@@ -28,6 +28,7 @@ impl Into<crate::errors::Error> for InvalidFunctionName {
 
         crate::errors::Error {
             source: Box::new(self),
+            code: "InvalidFunctionName",
             line: line,
             column: column,
             message,
@@ -31,6 +31,7 @@ impl Into<crate::errors::Error> for InvalidIncludePath {
 
         crate::errors::Error {
             source: Box::new(self),
+            code: "InvalidIncludePath",
             line: line,
             column: column,
             message,
@@ -0,0 +1,42 @@
+use std::fmt::Display;
+
+#[derive(Debug)]
+pub struct InvalidJson {
+    pub reason: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl InvalidJson {
+    pub fn new(reason: String, line: usize, column: usize) -> Self {
+        Self {
+            reason,
+            line,
+            column,
+        }
+    }
+}
+
+impl Display for InvalidJson {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid JSON: {}", self.reason)
+    }
+}
+
+impl std::error::Error for InvalidJson {}
+
+impl Into<crate::errors::Error> for InvalidJson {
+    fn into(self) -> crate::errors::Error {
+        let message = format!("Invalid JSON: {}", self.reason);
+        let line = self.line;
+        let column = self.column;
+
+        crate::errors::Error {
+            source: Box::new(self),
+            code: "InvalidJson",
+            line,
+            column,
+            message,
+        }
+    }
+}
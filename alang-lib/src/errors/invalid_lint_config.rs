@@ -0,0 +1,42 @@
+use std::fmt::Display;
+
+#[derive(Debug)]
+pub struct InvalidLintConfig {
+    pub reason: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl InvalidLintConfig {
+    pub fn new(reason: String, line: usize, column: usize) -> Self {
+        Self {
+            reason,
+            line,
+            column,
+        }
+    }
+}
+
+impl Display for InvalidLintConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid lint config: {}", self.reason)
+    }
+}
+
+impl std::error::Error for InvalidLintConfig {}
+
+impl Into<crate::errors::Error> for InvalidLintConfig {
+    fn into(self) -> crate::errors::Error {
+        let message = format!("Invalid lint config: {}", self.reason);
+        let line = self.line;
+        let column = self.column;
+
+        crate::errors::Error {
+            source: Box::new(self),
+            code: "InvalidLintConfig",
+            line,
+            column,
+            message,
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use std::fmt::Display;
+
+#[derive(Debug)]
+pub struct InvalidNumberLiteral {
+    pub literal: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl InvalidNumberLiteral {
+    pub fn new(literal: String, line: usize, column: usize) -> Self {
+        Self {
+            literal,
+            line,
+            column,
+        }
+    }
+}
+
+impl Display for InvalidNumberLiteral {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid number literal: '{}'", self.literal)
+    }
+}
+
+impl std::error::Error for InvalidNumberLiteral {}
+
+impl Into<crate::errors::Error> for InvalidNumberLiteral {
+    fn into(self) -> crate::errors::Error {
+        let message = format!("Invalid number literal: '{}'", self.literal);
+        let line = self.line;
+        let column = self.column;
+
+        crate::errors::Error {
+            source: Box::new(self),
+            code: "InvalidNumberLiteral",
+            line,
+            column,
+            message,
+        }
+    }
+}
@@ -34,6 +34,7 @@ impl Into<crate::errors::Error> for InvalidOperation {
 
         crate::errors::Error {
             source: Box::new(self),
+            code: "InvalidOperation",
             line,
             column,
             message,
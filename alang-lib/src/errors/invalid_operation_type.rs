@@ -66,6 +66,7 @@ impl Into<crate::errors::Error> for InvalidOperationType {
 
         crate::errors::Error {
             source: Box::new(self),
+            code: "InvalidOperationType",
             line,
             column,
             message,
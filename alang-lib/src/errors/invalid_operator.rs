@@ -35,6 +35,7 @@ impl Into<crate::errors::Error> for InvalidOperator {
 
         crate::errors::Error {
             source: Box::new(self),
+            code: "InvalidOperator",
             line: line,
             column: column,
             message,
@@ -34,6 +34,7 @@ impl Into<crate::errors::Error> for IOError {
 
         crate::errors::Error {
             source: Box::new(self),
+            code: "IOError",
             line: line,
             column: column,
             message,
@@ -13,6 +13,9 @@ pub use unhandled_character::UnhandledCharacter;
 mod invalid_escape_character;
 pub use invalid_escape_character::InvalidEscapeCharacter;
 
+mod invalid_escape_sequence;
+pub use invalid_escape_sequence::InvalidEscapeSequence;
+
 mod invalid_function_name;
 pub use invalid_function_name::InvalidFunctionName;
 
@@ -22,6 +25,9 @@ pub use invalid_include_path::InvalidIncludePath;
 mod invalid_argument_count;
 pub use invalid_argument_count::InvalidArgumentCount;
 
+mod invalid_argument_type;
+pub use invalid_argument_type::InvalidArgumentType;
+
 mod unexpected_eol;
 pub use unexpected_eol::UnexpectedEOL;
 
@@ -31,6 +37,12 @@ pub use unexpected_eof::UnexpectedEOF;
 mod invalid_character_literal;
 pub use invalid_character_literal::InvalidCharacterLiteral;
 
+mod invalid_number_literal;
+pub use invalid_number_literal::InvalidNumberLiteral;
+
+mod character_out_of_range;
+pub use character_out_of_range::CharacterOutOfRange;
+
 mod unexpected_token;
 pub use unexpected_token::UnexpectedToken;
 
@@ -55,13 +67,73 @@ pub use invalid_condition::InvalidCondition;
 mod io_error;
 pub use io_error::IOError;
 
+mod invalid_lint_config;
+pub use invalid_lint_config::InvalidLintConfig;
+
+mod invalid_json;
+pub use invalid_json::InvalidJson;
+
+mod undefined_member;
+pub use undefined_member::UndefinedMember;
+
+mod thrown;
+pub use thrown::Thrown;
+
+mod unmatched_value;
+pub use unmatched_value::UnmatchedValue;
+
 pub struct Error {
     source: Box<dyn std::error::Error>,
+    /// Name of the kind of error, set by each error type
+    code: &'static str,
     line: usize,
     column: usize,
     message: String,
 }
 
+impl Error {
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The name of the kind of error, e.g. `UndefinedVariable`, which scripts see
+    /// as the `code` of a caught error
+    pub fn code(&self) -> &str {
+        match self.thrown() {
+            Some(thrown) => thrown.code(),
+            None => self.code,
+        }
+    }
+
+    /// The error value of the `throw` that caused the error
+    pub fn thrown(&self) -> Option<&crate::types::error::ErrorVal> {
+        self.source
+            .downcast_ref::<Thrown>()
+            .map(|thrown| &thrown.value)
+    }
+
+    /// Whether the error was caused by the input ending too early,
+    /// e.g. an `if` or `function` block that has not been closed yet.
+    pub fn is_unexpected_eof(&self) -> bool {
+        if self.source.is::<UnexpectedEOF>() {
+            return true;
+        }
+
+        match self.source.downcast_ref::<UnexpectedToken>() {
+            Some(token) => token.provided == crate::token::TokenType::EOF,
+            None => false,
+        }
+    }
+}
+
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
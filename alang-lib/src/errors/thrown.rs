@@ -0,0 +1,46 @@
+use std::fmt::Display;
+
+use crate::types::{error::ErrorVal, RuntimeType, RuntimeValue};
+
+/// A `throw` that no `catch` has handled yet
+#[derive(Debug)]
+pub struct Thrown {
+    pub value: ErrorVal,
+}
+
+impl Thrown {
+    /// Error values are thrown as they are, anything else becomes the message
+    /// of a new error at the position of the `throw`
+    pub fn new(value: RuntimeType, line: usize, column: usize) -> Self {
+        let value = match value.value {
+            RuntimeValue::Error(error) => error,
+            value => ErrorVal::new(&value.to_string(), "Thrown", line, column),
+        };
+
+        Self { value }
+    }
+}
+
+impl Display for Thrown {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.value.message())
+    }
+}
+
+impl std::error::Error for Thrown {}
+
+impl Into<crate::errors::Error> for Thrown {
+    fn into(self) -> crate::errors::Error {
+        let message = self.value.message().to_string();
+        let line = self.value.line;
+        let column = self.value.column;
+
+        crate::errors::Error {
+            source: Box::new(self),
+            code: "Thrown",
+            line,
+            column,
+            message,
+        }
+    }
+}
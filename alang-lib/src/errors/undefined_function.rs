@@ -33,6 +33,7 @@ impl Into<crate::errors::Error> for UndefinedFunction {
 
         crate::errors::Error {
             source: Box::new(self),
+            code: "UndefinedFunction",
             line: line,
             column: column,
             message,
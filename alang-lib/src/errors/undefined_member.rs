@@ -0,0 +1,50 @@
+use std::fmt::Display;
+
+use crate::types::RuntimeType;
+
+#[derive(Debug)]
+pub struct UndefinedMember {
+    pub member: String,
+    pub value: RuntimeType,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl UndefinedMember {
+    pub fn new(member: String, value: RuntimeType, line: usize, column: usize) -> Self {
+        Self {
+            member,
+            value,
+            line,
+            column,
+        }
+    }
+}
+
+impl Display for UndefinedMember {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Undefined Member {:?} of {:?}",
+            self.member, self.value.value
+        )
+    }
+}
+
+impl std::error::Error for UndefinedMember {}
+
+impl Into<crate::errors::Error> for UndefinedMember {
+    fn into(self) -> crate::errors::Error {
+        let message = self.to_string();
+        let line = self.line;
+        let column = self.column;
+
+        crate::errors::Error {
+            source: Box::new(self),
+            code: "UndefinedMember",
+            line,
+            column,
+            message,
+        }
+    }
+}
@@ -33,6 +33,7 @@ impl Into<crate::errors::Error> for UndefinedVariable {
 
         crate::errors::Error {
             source: Box::new(self),
+            code: "UndefinedVariable",
             line: line,
             column: column,
             message,
@@ -28,6 +28,7 @@ impl Into<crate::errors::Error> for UnexpectedEOF {
 
         crate::errors::Error {
             source: Box::new(self),
+            code: "UnexpectedEOF",
             line: line,
             column: column,
             message,
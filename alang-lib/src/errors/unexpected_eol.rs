@@ -28,6 +28,7 @@ impl Into<crate::errors::Error> for UnexpectedEOL {
 
         crate::errors::Error {
             source: Box::new(self),
+            code: "UnexpectedEOL",
             line: line,
             column: column,
             message,
@@ -61,6 +61,7 @@ impl Into<crate::errors::Error> for UnexpectedExpression {
 
         crate::errors::Error {
             source: Box::new(self),
+            code: "UnexpectedExpression",
             line: line,
             column: column,
             message,
@@ -61,6 +61,7 @@ impl Into<crate::errors::Error> for UnexpectedToken {
 
         crate::errors::Error {
             source: Box::new(self),
+            code: "UnexpectedToken",
             line: line,
             column: column,
             message,
@@ -33,6 +33,7 @@ impl Into<crate::errors::Error> for UnhandledCharacter {
 
         crate::errors::Error {
             source: Box::new(self),
+            code: "UnhandledCharacter",
             line: line,
             column: column,
             message,
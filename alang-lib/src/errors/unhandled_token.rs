@@ -35,6 +35,7 @@ impl Into<crate::errors::Error> for UnhandledToken {
 
         crate::errors::Error {
             source: Box::new(self),
+            code: "UnhandledToken",
             line: line,
             column: column,
             message,
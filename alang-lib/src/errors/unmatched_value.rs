@@ -0,0 +1,45 @@
+use std::fmt::Display;
+
+use crate::types::RuntimeType;
+
+/// A `match` without a wildcard got a value none of its arms accept
+#[derive(Debug)]
+pub struct UnmatchedValue {
+    pub value: RuntimeType,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl UnmatchedValue {
+    pub fn new(value: RuntimeType, line: usize, column: usize) -> Self {
+        Self {
+            value,
+            line,
+            column,
+        }
+    }
+}
+
+impl Display for UnmatchedValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "No arm matches {:?}", self.value.value)
+    }
+}
+
+impl std::error::Error for UnmatchedValue {}
+
+impl Into<crate::errors::Error> for UnmatchedValue {
+    fn into(self) -> crate::errors::Error {
+        let message = format!("No arm matches {:?}", self.value.value);
+        let line = self.line;
+        let column = self.column;
+
+        crate::errors::Error {
+            source: Box::new(self),
+            code: "UnmatchedValue",
+            line,
+            column,
+            message,
+        }
+    }
+}
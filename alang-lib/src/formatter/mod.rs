@@ -0,0 +1,732 @@
+mod trivia;
+
+use crate::{
+    errors::Error,
+    lexer::tokenize_lossless,
+    parser::{
+        ast::{self, ExpressionType},
+        Parser,
+    },
+    types::{Operator, Relational},
+};
+
+use self::trivia::{Comment, Trivia};
+
+#[derive(Debug, Clone)]
+pub struct FormatOptions {
+    /// Spaces per indentation level
+    pub indent_width: usize,
+    /// Lines longer than this are continued on the next line with a `\`
+    pub line_width: usize,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            indent_width: 4,
+            line_width: 100,
+        }
+    }
+}
+
+/// Formats the source code into the canonical alang style, keeping the comments
+pub fn format(source: &str, options: &FormatOptions) -> Result<String, Error> {
+    let program = Parser::produce_ast(source)?;
+    let tokens = tokenize_lossless(source)?;
+
+    let mut printer = Printer::new(options, Trivia::new(source, &tokens), 0);
+    printer.block(&program.body);
+    printer.flush_comments(usize::MAX, 0);
+
+    Ok(printer.finish())
+}
+
+struct Printer<'a> {
+    options: &'a FormatOptions,
+    trivia: Trivia,
+    lines: Vec<String>,
+    indent: usize,
+    // Nothing has been written since the last block header
+    block_start: bool,
+}
+
+impl<'a> Printer<'a> {
+    fn new(options: &'a FormatOptions, trivia: Trivia, indent: usize) -> Self {
+        Self {
+            options,
+            trivia,
+            lines: Vec::new(),
+            indent,
+            block_start: true,
+        }
+    }
+
+    fn finish(mut self) -> String {
+        while let Some(true) = self.lines.last().map(|line| line.is_empty()) {
+            self.lines.pop();
+        }
+
+        if self.lines.is_empty() {
+            return String::new();
+        }
+
+        let mut output = self.lines.join("\n");
+        output.push('\n');
+        output
+    }
+
+    fn indentation(&self, extra: usize) -> String {
+        " ".repeat((self.indent + extra) * self.options.indent_width)
+    }
+
+    fn push(&mut self, text: &str) {
+        let line = format!("{}{}", self.indentation(0), text);
+        self.lines.push(line);
+        self.block_start = false;
+    }
+
+    // Keeps a single empty line where the source had one or more
+    fn blank_line_before(&mut self, line: usize) {
+        if self.block_start || !self.trivia.is_blank(line.saturating_sub(1)) {
+            return;
+        }
+
+        if let Some(false) = self.lines.last().map(|line| line.is_empty()) {
+            self.lines.push(String::new());
+        }
+    }
+
+    fn flush_comments(&mut self, line: usize, column: usize) {
+        while let Some(comment) = self.trivia.next_before(line, column) {
+            self.comment(comment);
+        }
+    }
+
+    fn comment(&mut self, comment: Comment) {
+        if comment.trailing {
+            if let Some(last) = self.lines.last_mut().filter(|line| !line.is_empty()) {
+                last.push(' ');
+                last.push_str(&comment.text);
+                return;
+            }
+        }
+
+        self.blank_line_before(comment.line);
+
+        // Lines inside block comments are kept as they are
+        let mut lines = comment.text.split('\n');
+        self.push(lines.next().unwrap_or_default());
+        self.lines.extend(lines.map(String::from));
+    }
+
+    fn block(&mut self, body: &[ast::Expression]) {
+        for expression in body {
+            self.statement(expression);
+        }
+    }
+
+    fn statement(&mut self, expression: &ast::Expression) {
+        self.flush_comments(expression.line, expression.column);
+        self.blank_line_before(expression.line);
+
+        match &expression.expression_type {
+            ExpressionType::Function(func) => self.function(String::new(), func),
+            ExpressionType::If(cond) => self.if_block(String::new(), cond),
+            ExpressionType::Try(try_block) => self.try_block(String::new(), try_block),
+            ExpressionType::Match(matching) => self.match_block(String::new(), matching),
+            _ => {
+                let chunks = self.chunks(expression);
+                let flat = chunks.join(" ");
+
+                if flat.contains('\n') || self.too_long(0, &flat) {
+                    if let Some((prefix, tail)) = self.block_tail(expression) {
+                        match &tail.expression_type {
+                            ExpressionType::Function(func) => self.function(prefix, func),
+                            ExpressionType::If(cond) => self.if_block(prefix, cond),
+                            ExpressionType::Try(try_block) => self.try_block(prefix, try_block),
+                            ExpressionType::Match(matching) => self.match_block(prefix, matching),
+                            _ => unreachable!(),
+                        }
+                        return;
+                    }
+                }
+
+                self.fill(chunks);
+            }
+        }
+    }
+
+    fn too_long(&self, extra: usize, text: &str) -> bool {
+        self.indentation(extra).len() + text.chars().count() > self.options.line_width
+    }
+
+    /// Finds a block at the end of an assignment or return, so it can be written
+    /// as `x = if ...` with the body on the following lines
+    fn block_tail<'e>(
+        &mut self,
+        expression: &'e ast::Expression,
+    ) -> Option<(String, &'e ast::Expression)> {
+        let (prefix, value) = match &expression.expression_type {
+            ExpressionType::Assignment(assignment) => (
+                format!("{} = ", self.chunks(&assignment.left).join(" ")),
+                &*assignment.right,
+            ),
+            ExpressionType::Return(ret) => ("return ".to_string(), &*ret.value),
+            _ => return None,
+        };
+
+        match &value.expression_type {
+            ExpressionType::Function(_)
+            | ExpressionType::If(_)
+            | ExpressionType::Try(_)
+            | ExpressionType::Match(_) => Some((prefix, value)),
+            _ => self
+                .block_tail(value)
+                .map(|(tail_prefix, tail)| (prefix + &tail_prefix, tail)),
+        }
+    }
+
+    /// Writes the chunks, continuing on the next line with a `\` when the line gets too long
+    fn fill(&mut self, chunks: Vec<String>) {
+        let mut line = self.indentation(0);
+        let mut empty = true;
+
+        for chunk in chunks {
+            let width = chunk.split('\n').next().unwrap_or_default().chars().count();
+
+            if !empty && line.chars().count() + 1 + width > self.options.line_width {
+                line.push_str(" \\");
+                self.lines.push(line);
+                line = self.indentation(1);
+                empty = true;
+            }
+
+            if !empty {
+                line.push(' ');
+            }
+            line.push_str(&chunk);
+            empty = false;
+
+            // Nested blocks span multiple lines, only the last one is still open
+            if let Some(index) = line.rfind('\n') {
+                let rest = line.split_off(index);
+                self.lines.extend(line.split('\n').map(String::from));
+                line = rest[1..].to_string();
+            }
+        }
+
+        self.lines.push(line);
+        self.block_start = false;
+    }
+
+    fn function(&mut self, prefix: String, func: &ast::Function) {
+        let name = self.chunks(&func.name).join(" ");
+        let mut header = self.call_chunks(&name, &func.parameters);
+        header[0] = format!("{}function {}", prefix, header[0]);
+
+        self.fill(header);
+        self.block_start = true;
+
+        self.indent += 1;
+        self.block(&func.body);
+        self.flush_comments(func.end_line, 0);
+        self.indent -= 1;
+
+        self.push("end");
+    }
+
+    fn if_block(&mut self, prefix: String, cond: &ast::If) {
+        self.if_header(format!("{}if ", prefix), &cond.condition);
+
+        self.indent += 1;
+        self.block(&cond.body);
+
+        let mut current = cond;
+        while let Some(else_body) = &current.else_body {
+            self.flush_comments(current.else_line.unwrap_or(current.end_line), 0);
+            self.indent -= 1;
+
+            if let [ast::Expression {
+                expression_type: ExpressionType::If(nested),
+                ..
+            }] = else_body.as_slice()
+            {
+                self.if_header("else if ".to_string(), &nested.condition);
+                self.indent += 1;
+                self.block(&nested.body);
+                current = nested;
+                continue;
+            }
+
+            self.push("else");
+            self.block_start = true;
+            self.indent += 1;
+            self.block(else_body);
+            break;
+        }
+
+        self.flush_comments(cond.end_line, 0);
+        self.indent -= 1;
+
+        self.push("end");
+    }
+
+    fn try_block(&mut self, prefix: String, try_block: &ast::Try) {
+        self.push(&format!("{}try", prefix));
+        self.block_start = true;
+
+        self.indent += 1;
+        self.block(&try_block.body);
+
+        if let Some(catch) = &try_block.catch {
+            self.flush_comments(catch.line, 0);
+            self.indent -= 1;
+
+            let name = self.chunks(&catch.name).join(" ");
+            self.push(&format!("catch {}", name));
+            self.block_start = true;
+            self.indent += 1;
+            self.block(&catch.body);
+        }
+
+        if let Some(finally) = &try_block.finally {
+            self.flush_comments(try_block.finally_line.unwrap_or(try_block.end_line), 0);
+            self.indent -= 1;
+
+            self.push("finally");
+            self.block_start = true;
+            self.indent += 1;
+            self.block(finally);
+        }
+
+        self.flush_comments(try_block.end_line, 0);
+        self.indent -= 1;
+
+        self.push("end");
+    }
+
+    fn match_block(&mut self, prefix: String, matching: &ast::Match) {
+        let mut header = self.chunks(&matching.value);
+        header[0] = format!("{}match {}", prefix, header[0]);
+        header.last_mut().unwrap().push_str(" with");
+
+        self.fill(header);
+        self.block_start = true;
+
+        self.indent += 1;
+        for arm in &matching.arms {
+            self.flush_comments(arm.line, arm.column);
+            self.blank_line_before(arm.line);
+
+            let chunks = self.arm_chunks(arm);
+            self.fill(chunks);
+        }
+        self.flush_comments(matching.end_line, 0);
+        self.indent -= 1;
+
+        self.push("end");
+    }
+
+    fn if_header(&mut self, prefix: String, condition: &ast::Expression) {
+        let mut header = self.chunks(condition);
+        header[0] = prefix + &header[0];
+        header.last_mut().unwrap().push_str(" then");
+
+        self.fill(header);
+        self.block_start = true;
+    }
+
+    /// Splits the expression into the pieces between which a line may be broken
+    fn chunks(&mut self, expression: &ast::Expression) -> Vec<String> {
+        match &expression.expression_type {
+            ExpressionType::Identifier(identifier) => vec![identifier.name.to_string()],
+            ExpressionType::Number(number) => {
+                match self.trivia.literal(expression.line, expression.column) {
+                    Some(literal) => vec![literal.to_string()],
+                    None => vec![number_text(*number)],
+                }
+            }
+            ExpressionType::String(string) => {
+                match self.trivia.literal(expression.line, expression.column) {
+                    Some(literal) => vec![literal.to_string()],
+                    None => vec![quote(string, '"')],
+                }
+            }
+            ExpressionType::Interpolation(interpolation) => {
+                let written = self
+                    .trivia
+                    .interpolation(expression.line, expression.column)
+                    .filter(|written| written.len() == interpolation.parts.len())
+                    .map(<[_]>::to_vec)
+                    .unwrap_or_default();
+
+                let mut string = String::from('"');
+                for (i, part) in interpolation.parts.iter().enumerate() {
+                    let written = written.get(i).cloned().flatten();
+
+                    match part {
+                        ast::InterpolationPart::Text(text) => match written {
+                            Some(written) => string.push_str(&written),
+                            None => escape(&mut string, text, '"'),
+                        },
+                        // Expressions with comments in them are kept as written
+                        ast::InterpolationPart::Expression(part) => {
+                            let code = written.unwrap_or_else(|| self.chunks(part).join(" "));
+                            string.push_str("${");
+                            string.push_str(&code);
+                            string.push('}');
+                        }
+                    }
+                }
+                string.push('"');
+
+                vec![string]
+            }
+            ExpressionType::Character(character) => {
+                match self.trivia.literal(expression.line, expression.column) {
+                    Some(literal) => vec![literal.to_string()],
+                    None => vec![quote(&character.to_string(), '\'')],
+                }
+            }
+            ExpressionType::Boolean(boolean) => vec![boolean.to_string()],
+            ExpressionType::Null => vec!["null".to_string()],
+            ExpressionType::Type(name) => vec![name.clone()],
+            ExpressionType::Assignment(assignment) => {
+                let left = self.chunks(&assignment.left);
+                let right = self.chunks(&assignment.right);
+                join(left, "=", right)
+            }
+            ExpressionType::Binary(binary) => {
+                let left = self.chunks(&binary.left);
+                let right = match &binary.operator {
+                    // The type keywords are parsed as literals of that type
+                    Operator::Relational(Relational::Is) => match type_name(&binary.right) {
+                        Some(name) => vec![name.to_string()],
+                        None => self.chunks(&binary.right),
+                    },
+                    _ => self.chunks(&binary.right),
+                };
+                join(left, &binary.operator.to_string(), right)
+            }
+            ExpressionType::Unary(unary) => {
+                let mut right = self.chunks(&unary.right);
+                right[0] = format!("{}{}", unary.operator, right[0]);
+                right
+            }
+            ExpressionType::Grouping(group) => {
+                let mut inner = self.chunks(&group.expression);
+                inner[0] = format!("({}", inner[0]);
+                inner.last_mut().unwrap().push(')');
+                inner
+            }
+            ExpressionType::Call(call) => {
+                let mut name = self.chunks(&call.name).join(" ");
+                if call.optional {
+                    name.push_str("?.");
+                }
+                self.call_chunks(&name, &call.parameters)
+            }
+            ExpressionType::Member(member) => {
+                let object = self.chunks(&member.object).join(" ");
+                let dot = if member.optional { "?." } else { "." };
+                vec![format!("{}{}{}", object, dot, member.name)]
+            }
+            ExpressionType::Include(include) => {
+                self.call_chunks("include", std::slice::from_ref(&include.path))
+            }
+            ExpressionType::Return(ret) => {
+                let mut value = self.chunks(&ret.value);
+                value[0] = format!("return {}", value[0]);
+                value
+            }
+            ExpressionType::Throw(throw) => {
+                let mut value = self.chunks(&throw.value);
+                value[0] = format!("throw {}", value[0]);
+                value
+            }
+            ExpressionType::If(cond) => match self.inline_if(cond) {
+                Some(inline) => vec![inline],
+                None => vec![self.nested(expression)],
+            },
+            ExpressionType::Try(try_block) => match self.inline_try(try_block, expression.line) {
+                Some(inline) => vec![inline],
+                None => vec![self.nested(expression)],
+            },
+            ExpressionType::Match(matching) => match self.inline_match(matching, expression.line) {
+                Some(inline) => vec![inline],
+                None => vec![self.nested(expression)],
+            },
+            ExpressionType::Function(_) => vec![self.nested(expression)],
+        }
+    }
+
+    // `pattern when guard => value`
+    fn arm_chunks(&mut self, arm: &ast::Arm) -> Vec<String> {
+        let mut chunks = match &arm.pattern {
+            ast::Pattern::Wildcard => vec!["_".to_string()],
+            ast::Pattern::Literal(literal) => self.chunks(literal),
+            ast::Pattern::Range(low, high) => {
+                vec![format!(
+                    "{}..{}",
+                    self.chunks(low).join(" "),
+                    self.chunks(high).join(" ")
+                )]
+            }
+            // The type keywords are parsed as literals of that type
+            ast::Pattern::Type(kind) => match type_name(kind) {
+                Some(name) => vec![format!("is {}", name)],
+                None => {
+                    let mut chunks = self.chunks(kind);
+                    chunks[0] = format!("is {}", chunks[0]);
+                    chunks
+                }
+            },
+            ast::Pattern::Binding(binding) => self.chunks(binding),
+        };
+
+        if let Some(guard) = &arm.guard {
+            chunks = join(chunks, "when", self.chunks(guard));
+        }
+
+        join(chunks, "=>", self.chunks(&arm.body))
+    }
+
+    fn call_chunks(&mut self, name: &str, arguments: &[ast::Expression]) -> Vec<String> {
+        if arguments.is_empty() {
+            return vec![format!("{}()", name)];
+        }
+
+        let mut chunks = Vec::new();
+        for (i, argument) in arguments.iter().enumerate() {
+            let mut argument = self.chunks(argument);
+            if i < arguments.len() - 1 {
+                argument.last_mut().unwrap().push(',');
+            }
+            chunks.extend(argument);
+        }
+
+        chunks[0] = format!("{}({}", name, chunks[0]);
+        chunks.last_mut().unwrap().push(')');
+
+        // Only break between the arguments when the call does not fit on a line by itself
+        let flat = chunks.join(" ");
+        if !flat.contains('\n') && !self.too_long(1, &flat) {
+            return vec![flat];
+        }
+
+        chunks
+    }
+
+    /// Writes an `if` on a single line, when it is short and simple enough
+    fn inline_if(&mut self, cond: &ast::If) -> Option<String> {
+        if self
+            .trivia
+            .has_comments_between(cond.condition.line, cond.end_line)
+        {
+            return None;
+        }
+
+        let inline = format!("{} end", self.inline_if_chain(cond)?);
+
+        if self.too_long(0, &inline) {
+            return None;
+        }
+
+        Some(inline)
+    }
+
+    // `if c then a else b` without the `end`, which an `else if` shares with its parent
+    fn inline_if_chain(&mut self, cond: &ast::If) -> Option<String> {
+        let mut inline = format!("if {} then", self.inline(&cond.condition)?);
+
+        if let Some(body) = self.inline_body(&cond.body)? {
+            inline = format!("{} {}", inline, body);
+        }
+
+        match cond.else_body.as_deref() {
+            None => {}
+            Some(
+                [ast::Expression {
+                    expression_type: ExpressionType::If(nested),
+                    ..
+                }],
+            ) => {
+                inline = format!("{} else {}", inline, self.inline_if_chain(nested)?);
+            }
+            Some(else_body) => {
+                inline.push_str(" else");
+                if let Some(body) = self.inline_body(else_body)? {
+                    inline = format!("{} {}", inline, body);
+                }
+            }
+        }
+
+        Some(inline)
+    }
+
+    /// Writes a `try` that was on a single line in the source back on one line,
+    /// when it is short and simple enough
+    fn inline_try(&mut self, try_block: &ast::Try, line: usize) -> Option<String> {
+        if line != try_block.end_line || self.trivia.has_comments_between(line, try_block.end_line)
+        {
+            return None;
+        }
+
+        let mut inline = "try".to_string();
+        if let Some(body) = self.inline_body(&try_block.body)? {
+            inline = format!("{} {}", inline, body);
+        }
+
+        if let Some(catch) = &try_block.catch {
+            inline = format!("{} catch {}", inline, self.inline(&catch.name)?);
+            if let Some(body) = self.inline_body(&catch.body)? {
+                inline = format!("{} {}", inline, body);
+            }
+        }
+
+        if let Some(finally) = &try_block.finally {
+            inline.push_str(" finally");
+            if let Some(body) = self.inline_body(finally)? {
+                inline = format!("{} {}", inline, body);
+            }
+        }
+
+        let inline = format!("{} end", inline);
+
+        if self.too_long(0, &inline) {
+            return None;
+        }
+
+        Some(inline)
+    }
+
+    /// Writes a `match` that was on a single line in the source back on one line,
+    /// with its arms separated by semicolons
+    fn inline_match(&mut self, matching: &ast::Match, line: usize) -> Option<String> {
+        if line != matching.end_line || self.trivia.has_comments_between(line, matching.end_line) {
+            return None;
+        }
+
+        let mut arms = Vec::new();
+        for arm in &matching.arms {
+            let arm = self.arm_chunks(arm).join(" ");
+            if arm.contains('\n') {
+                return None;
+            }
+            arms.push(arm);
+        }
+
+        let inline = format!(
+            "match {} with {} end",
+            self.inline(&matching.value)?,
+            arms.join("; ")
+        );
+
+        if self.too_long(0, &inline) {
+            return None;
+        }
+
+        Some(inline)
+    }
+
+    fn inline_body(&mut self, body: &[ast::Expression]) -> Option<Option<String>> {
+        match body {
+            [] => Some(None),
+            [expression] => self.inline(expression).map(Some),
+            _ => None,
+        }
+    }
+
+    fn inline(&mut self, expression: &ast::Expression) -> Option<String> {
+        if let ExpressionType::Function(_) = expression.expression_type {
+            return None;
+        }
+
+        let inline = self.chunks(expression).join(" ");
+
+        if inline.contains('\n') {
+            return None;
+        }
+
+        Some(inline)
+    }
+
+    /// Formats a block that is used inside of an expression on its own lines
+    fn nested(&mut self, expression: &ast::Expression) -> String {
+        let mut printer = Printer::new(self.options, self.trivia.without_comments(), self.indent);
+
+        match &expression.expression_type {
+            ExpressionType::Function(func) => printer.function(String::new(), func),
+            ExpressionType::If(cond) => printer.if_block(String::new(), cond),
+            ExpressionType::Try(try_block) => printer.try_block(String::new(), try_block),
+            ExpressionType::Match(matching) => printer.match_block(String::new(), matching),
+            _ => unreachable!(),
+        }
+
+        let indentation = self.indentation(0);
+        let lines = printer.lines.join("\n");
+        lines
+            .strip_prefix(&indentation)
+            .map(String::from)
+            .unwrap_or(lines)
+    }
+}
+
+fn join(mut left: Vec<String>, operator: &str, right: Vec<String>) -> Vec<String> {
+    let last = left.last_mut().unwrap();
+    last.push(' ');
+    last.push_str(operator);
+
+    left.extend(right);
+    left
+}
+
+fn type_name(expression: &ast::Expression) -> Option<&'static str> {
+    match expression.expression_type {
+        ExpressionType::Number(_) => Some("number"),
+        ExpressionType::String(_) => Some("string"),
+        ExpressionType::Boolean(_) => Some("boolean"),
+        ExpressionType::Character(_) => Some("character"),
+        _ => None,
+    }
+}
+
+/// Writes a number the way the lexer reads it back
+fn number_text(number: f64) -> String {
+    match number {
+        number if number.is_nan() => "NaN".to_string(),
+        number if number.is_infinite() && number > 0.0 => "Infinity".to_string(),
+        number if number.is_infinite() => "-Infinity".to_string(),
+        number => number.to_string(),
+    }
+}
+
+fn quote(value: &str, quote: char) -> String {
+    let mut quoted = String::from(quote);
+    escape(&mut quoted, value, quote);
+    quoted.push(quote);
+    quoted
+}
+
+/// Writes the text as it would appear between the quotes of a literal
+fn escape(quoted: &mut String, value: &str, quote: char) {
+    let mut chars = value.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            '\0' => quoted.push_str("\\0"),
+            '\\' => quoted.push_str("\\\\"),
+            c if c == quote => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            c if c.is_control() => quoted.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            // Would start an embedded expression
+            '$' if quote == '"' && chars.peek() == Some(&'{') => quoted.push_str("\\$"),
+            c => quoted.push(c),
+        }
+    }
+}
@@ -0,0 +1,264 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::{
+    lexer::{number_literal, raw_string_literal, tokenize_lossless},
+    token::{StringPart, Token, TokenType},
+};
+
+#[derive(Debug, Clone)]
+pub struct Comment {
+    pub text: String,
+    pub line: usize,
+    pub column: usize,
+    /// The comment follows code on the same line, e.g. `x = 1 // one`
+    pub trailing: bool,
+}
+
+/// The comments, blank lines and literal spellings of a source file, which the
+/// parser throws away
+pub struct Trivia {
+    comments: VecDeque<Comment>,
+    content_lines: HashSet<usize>,
+    /// Number, string and character literals as written, e.g. `0xFF`, `r"C:\path"`
+    /// or `'\x41'`, by position
+    literals: HashMap<(usize, usize), String>,
+    /// The parts of interpolated strings as written, by the position of the string.
+    /// Embedded expressions are only kept when they hold a comment.
+    interpolations: HashMap<(usize, usize), Vec<Option<String>>>,
+}
+
+impl Trivia {
+    /// Collects the trivia from the source and the tokens `tokenize_lossless` produced for it
+    pub fn new(source: &str, tokens: &VecDeque<Token>) -> Self {
+        let source = Source::new(source);
+        let mut comments = VecDeque::new();
+        let mut content_lines = HashSet::new();
+        let mut literals = HashMap::new();
+        let mut interpolations = HashMap::new();
+        let mut previous: Option<&Token> = None;
+
+        for token in tokens {
+            if let TokenType::Whitespace(_) = token.token_type {
+                continue;
+            }
+
+            // Strings can span lines, they end where the next token starts
+            if let Some(Token {
+                token_type: TokenType::String(_) | TokenType::Interpolation(_),
+                line,
+                ..
+            }) = previous
+            {
+                let end = match token.token_type {
+                    TokenType::EOL | TokenType::EOF => token.line - 1,
+                    _ => token.line,
+                };
+                content_lines.extend(*line..=end);
+            }
+
+            match &token.token_type {
+                TokenType::EOL | TokenType::EOF => {}
+                TokenType::Comment(text) | TokenType::DocComment(text) => {
+                    let trailing = match previous {
+                        Some(previous) => {
+                            previous.token_type != TokenType::EOL && previous.line == token.line
+                        }
+                        None => false,
+                    };
+
+                    let lines = text.matches('\n').count();
+                    content_lines.extend(token.line..=token.line + lines);
+
+                    comments.push_back(Comment {
+                        text: text.trim_end().to_string(),
+                        line: token.line,
+                        column: token.column,
+                        trailing,
+                    });
+                }
+                _ => {
+                    content_lines.insert(token.line);
+                    collect_literals(&source, token, &mut literals, &mut interpolations);
+                }
+            }
+
+            previous = Some(token);
+        }
+
+        Self {
+            comments,
+            content_lines,
+            literals,
+            interpolations,
+        }
+    }
+
+    /// The same blank lines, but without any of the comments
+    pub fn without_comments(&self) -> Self {
+        Self {
+            comments: VecDeque::new(),
+            content_lines: self.content_lines.clone(),
+            literals: self.literals.clone(),
+            interpolations: self.interpolations.clone(),
+        }
+    }
+
+    /// The number, string or character literal at the position, as it was written
+    pub fn literal(&self, line: usize, column: usize) -> Option<&str> {
+        self.literals.get(&(line, column)).map(String::as_str)
+    }
+
+    /// The parts of the interpolated string at the position: the text as it was
+    /// written, and the source of the embedded expressions that hold a comment
+    pub fn interpolation(&self, line: usize, column: usize) -> Option<&[Option<String>]> {
+        self.interpolations.get(&(line, column)).map(Vec::as_slice)
+    }
+
+    /// Removes and returns the next comment when it starts before the given position
+    pub fn next_before(&mut self, line: usize, column: usize) -> Option<Comment> {
+        let comment = self.comments.front()?;
+
+        if (comment.line, comment.column) < (line, column) {
+            self.comments.pop_front()
+        } else {
+            None
+        }
+    }
+
+    /// Whether any comment starts between the two lines, inclusive
+    pub fn has_comments_between(&self, start: usize, end: usize) -> bool {
+        self.comments
+            .iter()
+            .any(|comment| comment.line >= start && comment.line <= end)
+    }
+
+    /// Whether the line has nothing but whitespace on it
+    pub fn is_blank(&self, line: usize) -> bool {
+        line > 0 && !self.content_lines.contains(&line)
+    }
+}
+
+/// The source text, addressed by the lines and columns of tokens
+struct Source<'a> {
+    text: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> Source<'a> {
+    fn new(text: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+
+        Self { text, line_starts }
+    }
+
+    /// The text from the position to the end of the source
+    fn at(&self, line: usize, column: usize) -> Option<&'a str> {
+        Some(&self.text[self.offset(line, column)?..])
+    }
+
+    /// Byte offset of the position
+    fn offset(&self, line: usize, column: usize) -> Option<usize> {
+        let start = *self.line_starts.get(line.checked_sub(1)?)?;
+        let (offset, _) = self.text[start..]
+            .char_indices()
+            .nth(column.checked_sub(1)?)?;
+
+        Some(start + offset)
+    }
+}
+
+/// Records how the literals in the token, or embedded in it, were written
+fn collect_literals(
+    source: &Source,
+    token: &Token,
+    literals: &mut HashMap<(usize, usize), String>,
+    interpolations: &mut HashMap<(usize, usize), Vec<Option<String>>>,
+) {
+    let text = source.at(token.line, token.column).unwrap_or_default();
+
+    let literal = match &token.token_type {
+        // `Infinity` and `NaN` are keywords and have no other spelling
+        TokenType::Number(_) if text.starts_with(|c: char| c.is_ascii_digit() || c == '.') => {
+            number_literal(text).0
+        }
+        TokenType::String(_) if text.starts_with('r') => match raw_string_literal(text) {
+            Some((literal, _)) => literal,
+            None => return,
+        },
+        TokenType::String(_) if text.starts_with('"') => {
+            &text[..quoted_text(&text[1..], '"').len() + 2]
+        }
+        TokenType::Character(_) if text.starts_with('\'') => {
+            &text[..quoted_text(&text[1..], '\'').len() + 2]
+        }
+        TokenType::Interpolation(parts) => {
+            let mut spelled = Vec::new();
+            // Skip the opening quote
+            let mut rest = &text[1..];
+
+            for part in parts {
+                match part {
+                    StringPart::Text(_) => {
+                        let written = quoted_text(rest, '"');
+                        rest = &rest[written.len()..];
+                        spelled.push(Some(written.to_string()));
+                    }
+                    StringPart::Code(tokens) => {
+                        for token in tokens {
+                            collect_literals(source, token, literals, interpolations);
+                        }
+
+                        // The code ends at the closing brace, where its `EOF` token is
+                        let start = source.text.len() - rest.len();
+                        let end = tokens
+                            .last()
+                            .and_then(|end| source.offset(end.line, end.column))
+                            .unwrap_or(start);
+                        let code = source.text.get(start + 2..end).unwrap_or_default();
+                        rest = source.text.get(end + 1..).unwrap_or_default();
+
+                        spelled.push(has_comment(code).then(|| code.to_string()));
+                    }
+                }
+            }
+
+            interpolations.insert((token.line, token.column), spelled);
+            return;
+        }
+        _ => return,
+    };
+
+    literals.insert((token.line, token.column), literal.to_string());
+}
+
+/// The text of a quoted literal up to its closing quote, or the `${` of an embedded
+/// expression, with the escapes left as they are
+fn quoted_text(text: &str, quote: char) -> &str {
+    let mut chars = text.char_indices();
+
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '$' if quote == '"' && text[index + 1..].starts_with('{') => return &text[..index],
+            c if c == quote => return &text[..index],
+            _ => {}
+        }
+    }
+
+    text
+}
+
+fn has_comment(code: &str) -> bool {
+    tokenize_lossless(code).is_ok_and(|tokens| {
+        tokens.iter().any(|token| {
+            matches!(
+                token.token_type,
+                TokenType::Comment(_) | TokenType::DocComment(_)
+            )
+        })
+    })
+}
@@ -0,0 +1,90 @@
+use crate::{
+    errors::{Error, InvalidArgumentType},
+    types::{
+        boolean::BooleanVal, character::CharacterVal, native_function::NativeFunctionVal,
+        string::StringVal, RuntimeType, RuntimeValue,
+    },
+};
+
+use super::Environment;
+
+/// Installs the built-ins every program has:
+///
+/// - `is_digit(c)` whether the character is one of the digits `'0'` to `'9'`
+/// - `is_alpha(c)` whether the character is a letter, in any script
+/// - `to_upper(value)` the upper case of a character or string. Characters whose
+///   upper case takes more than one character, like `'ß'`, become a string.
+/// - `help(f)` the signature of a function followed by its `///` doc comments
+pub fn install(env: &mut Environment) {
+    let is_digit = NativeFunctionVal::new("is_digit", Some(1), |args| {
+        let c = character_argument("is_digit", &args[0])?;
+        Ok(RuntimeValue::Boolean(BooleanVal::from(c.is_ascii_digit())))
+    });
+
+    let is_alpha = NativeFunctionVal::new("is_alpha", Some(1), |args| {
+        let c = character_argument("is_alpha", &args[0])?;
+        Ok(RuntimeValue::Boolean(BooleanVal::from(c.is_alphabetic())))
+    });
+
+    let to_upper = NativeFunctionVal::new("to_upper", Some(1), |args| match &args[0].value {
+        RuntimeValue::String(value) => Ok(RuntimeValue::String(StringVal::from(
+            value.value().to_uppercase(),
+        ))),
+        RuntimeValue::Character(value) => {
+            let mut upper = value.value().to_uppercase();
+
+            Ok(match (upper.next(), upper.len()) {
+                (Some(c), 0) => RuntimeValue::Character(CharacterVal::from(c)),
+                _ => {
+                    RuntimeValue::String(StringVal::from(value.value().to_uppercase().to_string()))
+                }
+            })
+        }
+        _ => {
+            Err(InvalidArgumentType::new("to_upper", args[0].clone(), "character or string").into())
+        }
+    });
+
+    let help = NativeFunctionVal::new("help", Some(1), |args| {
+        let (name, parameters, doc) = match &args[0].value {
+            RuntimeValue::Function(function) => {
+                (function.name(), function.parameters(), function.doc())
+            }
+            RuntimeValue::Closure(closure) => (
+                closure.prototype.name,
+                closure.parameters().to_vec(),
+                closure.doc(),
+            ),
+            RuntimeValue::NativeFunction(function) => {
+                return Ok(RuntimeValue::String(StringVal::from(format!(
+                    "{}(...)\n\nBuilt-in function",
+                    function.name
+                ))))
+            }
+            _ => return Err(InvalidArgumentType::new("help", args[0].clone(), "function").into()),
+        };
+
+        let parameters: Vec<&str> = parameters
+            .iter()
+            .map(|parameter| parameter.as_str())
+            .collect();
+        let mut help = format!("{}({})", name, parameters.join(", "));
+        if let Some(doc) = doc {
+            help.push_str("\n\n");
+            help.push_str(doc);
+        }
+
+        Ok(RuntimeValue::String(StringVal::from(help)))
+    });
+
+    for function in [is_digit, is_alpha, to_upper, help] {
+        env.set(function.name, RuntimeValue::NativeFunction(function), false);
+    }
+}
+
+fn character_argument(name: &str, argument: &RuntimeType) -> Result<char, Error> {
+    match &argument.value {
+        RuntimeValue::Character(value) => Ok(value.value()),
+        _ => Err(InvalidArgumentType::new(name, argument.clone(), "character").into()),
+    }
+}
@@ -1,18 +1,23 @@
-use std::collections::HashMap;
+use std::rc::Rc;
 
-use crate::types::RuntimeValue;
+use crate::{
+    symbol::{Symbol, SymbolMap},
+    types::RuntimeValue,
+};
 
+/// Parent scopes are shared between copies of an environment and only
+/// copied when a variable in them is assigned
 #[derive(Debug, Clone)]
 pub struct Environment {
-    parent: Option<Box<Environment>>,
-    pub(crate) variables: HashMap<String, RuntimeValue>,
-    constants: Vec<String>,
+    parent: Option<Rc<Environment>>,
+    pub(crate) variables: SymbolMap<RuntimeValue>,
+    constants: Vec<Symbol>,
 }
 
 impl Environment {
     pub fn new() -> Self {
         // Default global environment
-        let variables = HashMap::new();
+        let variables = SymbolMap::default();
 
         Environment {
             parent: None,
@@ -23,47 +28,54 @@ impl Environment {
 
     pub fn new_with_parent(parent: Environment) -> Self {
         Environment {
-            parent: Some(Box::new(parent)),
-            variables: HashMap::new(),
+            parent: Some(Rc::new(parent)),
+            variables: SymbolMap::default(),
             constants: Vec::new(),
         }
     }
 
     pub fn with_default_scope(mut self) -> Self {
         self.variables.insert(
-            "PI".to_string(),
+            Symbol::intern("PI"),
             RuntimeValue::Number(std::f64::consts::PI.into()),
         );
+        super::builtins::install(&mut self);
         self
     }
 
-    pub fn set(&mut self, symbol: &str, value: RuntimeValue, is_const: bool) -> RuntimeValue {
-        if self.variables.contains_key(symbol) {
+    pub fn set(
+        &mut self,
+        symbol: impl Into<Symbol>,
+        value: RuntimeValue,
+        is_const: bool,
+    ) -> RuntimeValue {
+        let symbol = symbol.into();
+
+        if self.variables.contains_key(&symbol) {
             self.assign(symbol, value.clone());
             return value;
         }
 
-        self.variables.insert(symbol.to_string(), value.clone());
+        self.variables.insert(symbol, value.clone());
 
         if is_const {
-            self.constants.push(symbol.to_string());
+            self.constants.push(symbol);
         }
 
         value
     }
 
-    pub fn assign(&mut self, symbol: &str, value: RuntimeValue) -> RuntimeValue {
+    pub fn assign(&mut self, symbol: impl Into<Symbol>, value: RuntimeValue) -> RuntimeValue {
+        let symbol = symbol.into();
         let env = self.resolve(symbol);
 
         match env {
             Some(e) => {
-                let symbol = symbol.to_owned();
-
                 if e.constants.contains(&symbol) {
                     panic!("Cannot assign to constant {}", symbol);
                 }
 
-                e.variables.insert(symbol.clone(), value.clone());
+                e.variables.insert(symbol, value.clone());
 
                 value
             }
@@ -71,23 +83,49 @@ impl Environment {
         }
     }
 
-    pub fn resolve(&mut self, symbol: &str) -> Option<&mut Environment> {
-        if self.variables.contains_key(symbol) {
+    pub fn resolve(&mut self, symbol: impl Into<Symbol>) -> Option<&mut Environment> {
+        let symbol = symbol.into();
+
+        if self.variables.contains_key(&symbol) {
             return Some(self);
         }
 
         match &mut self.parent {
-            Some(p) => p.resolve(symbol),
-            None => None,
+            Some(p) if p.lookup(symbol).is_some() => Rc::make_mut(p).resolve(symbol),
+            _ => None,
         }
     }
 
-    pub fn get(&mut self, symbol: &str) -> Option<RuntimeValue> {
-        let env = self.resolve(symbol);
+    /// Looks a variable up in the scope `depth` levels above this one, without searching
+    pub fn get_at(&self, depth: usize, symbol: impl Into<Symbol>) -> Option<RuntimeValue> {
+        let symbol = symbol.into();
 
-        match env {
-            Some(e) => e.variables.get(symbol).map(|v| v.clone()),
-            None => None,
+        match depth {
+            0 => self.variables.get(&symbol).cloned(),
+            _ => self.parent.as_ref()?.get_at(depth - 1, symbol),
         }
     }
+
+    /// Like `get`, but without needing mutable access
+    pub fn lookup(&self, symbol: impl Into<Symbol>) -> Option<&RuntimeValue> {
+        let symbol = symbol.into();
+
+        match self.variables.get(&symbol) {
+            Some(value) => Some(value),
+            None => self.parent.as_ref()?.lookup(symbol),
+        }
+    }
+
+    pub fn parent(&self) -> Option<&Environment> {
+        self.parent.as_deref()
+    }
+
+    /// Variables declared directly in this scope, not including any parent scopes.
+    pub fn iter(&self) -> impl Iterator<Item = (&Symbol, &RuntimeValue)> {
+        self.variables.iter()
+    }
+
+    pub fn get(&mut self, symbol: impl Into<Symbol>) -> Option<RuntimeValue> {
+        self.lookup(symbol).cloned()
+    }
 }
@@ -1,17 +1,22 @@
+mod builtins;
 pub mod environment;
+mod resolver;
 
-use std::io::Read;
+use std::{io::Read, rc::Rc};
 
 use crate::{
     errors::*,
     parser::{ast, Parser},
+    symbol::Symbol,
     types::{
-        boolean::BooleanVal, character::CharacterVal, function::FunctionVal, number::NumberVal,
-        string::StringVal, Arithmatic, BinaryOperation, BinaryOperationError, Logical, Operator,
-        RuntimeType, RuntimeValue,
+        boolean::BooleanVal, character::CharacterVal, error::ErrorVal, function::FunctionVal,
+        number::NumberVal, string::StringVal, Arithmatic, BinaryOperation, BinaryOperationError,
+        Logical, Operator, Relational, RuntimeType, RuntimeValue,
     },
+    vm,
 };
 pub use environment::Environment;
+pub use resolver::resolve;
 
 pub fn run(ast: &ast::Program, env: &mut Environment) -> Result<RuntimeValue, Error> {
     for expression in &ast.body {
@@ -25,6 +30,99 @@ pub fn run(ast: &ast::Program, env: &mut Environment) -> Result<RuntimeValue, Er
     Ok(RuntimeValue::Null)
 }
 
+/// Runs a program and returns the value of the last evaluated expression,
+/// or the returned value when the program hits a `return`.
+pub fn evaluate(ast: &ast::Program, env: &mut Environment) -> Result<RuntimeType, Error> {
+    let mut last_value = RuntimeType {
+        value: RuntimeValue::Null,
+        line: 0,
+        column: 0,
+    };
+
+    for expression in &ast.body {
+        last_value = evaluate_expression(expression, env)?;
+        if let RuntimeValue::Return(value) = last_value.value {
+            return Ok(*value);
+        }
+    }
+
+    Ok(last_value)
+}
+
+/// Calls a function value with already evaluated arguments,
+/// `name` is the name the function was called by and is used in errors.
+pub fn call_function(
+    name: &str,
+    func: &RuntimeValue,
+    args: Vec<RuntimeType>,
+    line: usize,
+    column: usize,
+) -> Result<RuntimeType, Error> {
+    let func = match func {
+        RuntimeValue::Function(func) => func,
+        RuntimeValue::NativeFunction(func) => {
+            if let Some(arity) = func.arity {
+                if arity != args.len() {
+                    return Err(
+                        InvalidArgumentCount::new(name, args.len(), arity, line, column).into(),
+                    );
+                }
+            }
+
+            return Ok(RuntimeType {
+                value: func.call(args)?,
+                line,
+                column,
+            });
+        }
+        RuntimeValue::Closure(closure) => return vm::call(name, closure, args, line, column),
+        _ => return Err(InvalidFunctionName::new(line, column).into()),
+    };
+
+    let mut new_env = func.env.clone();
+
+    let params = func.declaration.parameters.iter();
+
+    if params.len() != args.len() {
+        return Err(InvalidArgumentCount::new(name, args.len(), params.len(), line, column).into());
+    }
+
+    for (i, arg) in params.enumerate() {
+        let arg = match &arg.expression_type {
+            ast::ExpressionType::Identifier(arg) => &arg.name,
+            _ => unreachable!(),
+        };
+
+        new_env.set(arg, args[i].value.clone(), false);
+    }
+
+    let mut last_value = RuntimeValue::Null;
+    for expression in &func.declaration.body {
+        last_value = evaluate_expression(expression, &mut new_env)?.value;
+        if let RuntimeValue::Return(value) = last_value {
+            let v = *value;
+            return Ok(v);
+        }
+    }
+
+    Ok(RuntimeType {
+        value: last_value,
+        line,
+        column,
+    })
+}
+
+/// Looks the identifier up directly in the scope the resolver found it in,
+/// or by searching the enclosing scopes when that is not known
+fn lookup(identifier: &ast::Identifier, env: &mut Environment) -> Option<RuntimeValue> {
+    match identifier.depth {
+        Some(depth) => env
+            .get_at(depth, identifier.name)
+            .or_else(|| env.get(identifier.name)),
+        None => env.get(identifier.name),
+    }
+}
+
 fn evaluate_expression(
     expression: &ast::Expression,
     env: &mut Environment,
@@ -32,9 +130,14 @@ fn evaluate_expression(
     let expr_type = &expression.expression_type;
 
     match expr_type {
-        ast::ExpressionType::Identifier(value) => {
-            let value = env.get(&value).ok_or_else(|| {
-                UndefinedVariable::new(value.clone(), expression.line, expression.column).into()
+        ast::ExpressionType::Identifier(identifier) => {
+            let value = lookup(identifier, env).ok_or_else(|| {
+                UndefinedVariable::new(
+                    identifier.name.to_string(),
+                    expression.line,
+                    expression.column,
+                )
+                .into()
             })?;
 
             Ok(RuntimeType {
@@ -53,6 +156,25 @@ fn evaluate_expression(
             line: expression.line,
             column: expression.column,
         }),
+        ast::ExpressionType::Interpolation(interpolation) => {
+            let mut string = String::new();
+
+            for part in &interpolation.parts {
+                match part {
+                    ast::InterpolationPart::Text(text) => string.push_str(text),
+                    ast::InterpolationPart::Expression(part) => {
+                        let value = evaluate_expression(part, env)?;
+                        string.push_str(&value.to_string());
+                    }
+                }
+            }
+
+            Ok(RuntimeType {
+                value: RuntimeValue::String(StringVal::from(string)),
+                line: expression.line,
+                column: expression.column,
+            })
+        }
         ast::ExpressionType::Character(value) => Ok(RuntimeType {
             value: RuntimeValue::Character(CharacterVal::from(*value)),
             line: expression.line,
@@ -63,12 +185,17 @@ fn evaluate_expression(
             line: expression.line,
             column: expression.column,
         }),
+        ast::ExpressionType::Null => Ok(RuntimeType {
+            value: RuntimeValue::Null,
+            line: expression.line,
+            column: expression.column,
+        }),
         ast::ExpressionType::Assignment(assignment) => {
             let key = &*assignment.left;
             let value = evaluate_expression(&assignment.right, env)?;
 
             let key = match &key.expression_type {
-                ast::ExpressionType::Identifier(key) => key,
+                ast::ExpressionType::Identifier(key) => &key.name,
                 _ => return Err(InvalidAssignment::new(expression.line, expression.column).into()),
             };
 
@@ -78,91 +205,35 @@ fn evaluate_expression(
         }
         ast::ExpressionType::Binary(binary) => {
             let left = evaluate_expression(&binary.left, env)?;
-            let right = evaluate_expression(&binary.right, env)?;
-
-            let operator = &binary.operator;
-
-            match left.value.operation(&right.value, operator.clone()) {
-                Ok(value) => Ok(RuntimeType {
-                    value,
-                    line: expression.line,
-                    column: expression.column,
-                }),
-                Err(error) => match error {
-                    BinaryOperationError::InvalidOperation => {
-                        Err(InvalidOperation::new(left, right, operator.clone()).into())
-                    }
-                    BinaryOperationError::InvalidOperationType => {
-                        Err(InvalidOperationType::new(left, Some(right), operator.clone()).into())
-                    }
-                    BinaryOperationError::InvalidOperator => Err(InvalidOperator::new(
-                        operator.clone(),
-                        expression.line,
-                        expression.column,
-                    )
-                    .into()),
-                },
-            }
-        }
-        ast::ExpressionType::Unary(unary) => {
-            let raw_value = evaluate_expression(&unary.right, env)?;
-            let operator = &unary.operator;
-
-            // ensure value is a number
-            let value = match &raw_value.value {
-                RuntimeValue::Number(value) => Some(value),
-                _ => None,
-            };
 
-            if value.is_some() {
-                let value = value.unwrap();
-                return match operator {
-                    Operator::Arithmatic(Arithmatic::Minus) => Ok(RuntimeType {
-                        value: RuntimeValue::Number((-value.value).into()),
-                        line: expression.line,
-                        column: expression.column,
-                    }),
-                    Operator::Arithmatic(Arithmatic::Plus) => Ok(RuntimeType {
-                        value: RuntimeValue::Number((value.value).into()),
+            if let Operator::Logical(Logical::Coalesce) = binary.operator {
+                if !matches!(left.value, RuntimeValue::Null) {
+                    return Ok(RuntimeType {
+                        value: left.value,
                         line: expression.line,
                         column: expression.column,
-                    }),
-                    _ => Err(InvalidOperator::new(
-                        operator.clone(),
-                        expression.line,
-                        expression.column,
-                    )
-                    .into()),
-                };
+                    });
+                }
             }
 
-            let value = match &raw_value.value {
-                RuntimeValue::Boolean(value) => Some(value),
-                _ => None,
-            };
+            let right = evaluate_expression(&binary.right, env)?;
 
-            if value.is_some() {
-                let value = value.unwrap();
-                return match operator {
-                    Operator::Logical(Logical::Not) => Ok(RuntimeType {
-                        value: RuntimeValue::Boolean((!value.value).into()),
-                        line: expression.line,
-                        column: expression.column,
-                    }),
-                    _ => Err(InvalidOperator::new(
-                        operator.clone(),
-                        expression.line,
-                        expression.column,
-                    )
-                    .into()),
-                };
-            }
+            binary_operation(
+                left,
+                right,
+                &binary.operator,
+                expression.line,
+                expression.column,
+            )
+        }
+        ast::ExpressionType::Unary(unary) => {
+            let value = evaluate_expression(&unary.right, env)?;
 
-            Err(InvalidOperationType::new(raw_value, None, operator.clone()).into())
+            unary_operation(value, &unary.operator, expression.line, expression.column)
         }
 
         ast::ExpressionType::Type(t) => Ok(RuntimeType {
-            value: RuntimeValue::Type(t.to_owned()),
+            value: RuntimeValue::Type(t.as_str().into()),
             line: expression.line,
             column: expression.column,
         }),
@@ -186,9 +257,9 @@ fn evaluate_expression(
                 let mut last_value = RuntimeValue::Null;
                 for expression in &cond.body {
                     last_value = evaluate_expression(expression, env)?.value;
-                    if let RuntimeValue::Return(value) = last_value {
-                        let v = *value;
-                        return Ok(v);
+                    // Let the return bubble up to the enclosing function
+                    if let RuntimeValue::Return(_) = last_value {
+                        break;
                     }
                 }
                 return Ok(RuntimeType {
@@ -200,9 +271,8 @@ fn evaluate_expression(
                 let mut last_value = RuntimeValue::Null;
                 for expression in else_body {
                     last_value = evaluate_expression(expression, env)?.value;
-                    if let RuntimeValue::Return(value) = last_value {
-                        let v = *value;
-                        return Ok(v);
+                    if let RuntimeValue::Return(_) = last_value {
+                        break;
                     }
                 }
                 return Ok(RuntimeType {
@@ -221,16 +291,16 @@ fn evaluate_expression(
         ast::ExpressionType::Function(func) => {
             let name = func.name.clone();
             let name = match &name.expression_type {
-                ast::ExpressionType::Identifier(name) => name,
+                ast::ExpressionType::Identifier(name) => &name.name,
                 _ => return Err(InvalidFunctionName::new(name.line, name.column).into()),
             };
 
             let func = RuntimeValue::Function(FunctionVal::new(
-                func.clone(),
+                Rc::new(func.clone()),
                 Environment::new_with_parent(env.clone()),
             ));
 
-            env.set(&name, func.clone(), false);
+            env.set(*name, func.clone(), false);
 
             Ok(RuntimeType {
                 value: func,
@@ -253,13 +323,20 @@ fn evaluate_expression(
                 _ => return Err(InvalidFunctionName::new(raw_name.line, raw_name.column).into()),
             };
 
-            let func = env.get(name);
+            let func = lookup(name, env);
 
-            let func = match &func {
+            let func = match func {
+                Some(RuntimeValue::Null) if call.optional => {
+                    return Ok(RuntimeType {
+                        value: RuntimeValue::Null,
+                        line: expression.line,
+                        column: expression.column,
+                    })
+                }
                 Some(func) => func,
                 None => {
                     return Err(UndefinedFunction::new(
-                        name.clone(),
+                        name.name.to_string(),
                         raw_name.line,
                         raw_name.column,
                     )
@@ -267,54 +344,122 @@ fn evaluate_expression(
                 }
             };
 
-            let func = match &func {
-                RuntimeValue::Function(func) => func,
-                _ => return Err(InvalidFunctionName::new(raw_name.line, raw_name.column).into()),
-            };
+            if !matches!(
+                func,
+                RuntimeValue::Function(_)
+                    | RuntimeValue::NativeFunction(_)
+                    | RuntimeValue::Closure(_)
+            ) {
+                return Err(InvalidFunctionName::new(raw_name.line, raw_name.column).into());
+            }
 
             let mut args = Vec::new();
             for arg in &call.parameters {
                 args.push(evaluate_expression(arg, env)?);
             }
 
-            let mut new_env = func.env.clone();
+            call_function(
+                name.name.as_str(),
+                &func,
+                args,
+                raw_name.line,
+                raw_name.column,
+            )
+        }
+        ast::ExpressionType::Member(member) => {
+            let object = evaluate_expression(&member.object, env)?;
+
+            member_access(
+                object,
+                member.name,
+                member.optional,
+                expression.line,
+                expression.column,
+            )
+        }
+        ast::ExpressionType::Throw(throw) => {
+            let value = evaluate_expression(&throw.value, env)?;
 
-            let params = func.declaration.parameters.iter();
+            Err(Thrown::new(value, expression.line, expression.column).into())
+        }
+        ast::ExpressionType::Try(try_block) => {
+            let result = match evaluate_block(&try_block.body, env) {
+                Err(error) => match &try_block.catch {
+                    Some(catch) => {
+                        if let ast::ExpressionType::Identifier(name) = &catch.name.expression_type {
+                            let error = RuntimeValue::Error(ErrorVal::from(&error));
+                            env.set(name.name, error, false);
+                        }
+                        evaluate_block(&catch.body, env)
+                    }
+                    None => Err(error),
+                },
+                result => result,
+            };
 
-            if params.len() != args.len() {
-                return Err(InvalidArgumentCount::new(
-                    &name,
-                    args.len(),
-                    params.len(),
-                    raw_name.line,
-                    raw_name.column,
-                )
-                .into());
+            // The finally runs however the rest ended, its own value is dropped
+            if let Some(finally) = &try_block.finally {
+                evaluate_block(finally, env)?;
             }
 
-            for (i, arg) in params.enumerate() {
-                let arg = match &arg.expression_type {
-                    ast::ExpressionType::Identifier(arg) => arg,
-                    _ => unreachable!(),
+            Ok(RuntimeType {
+                value: result?,
+                line: expression.line,
+                column: expression.column,
+            })
+        }
+        ast::ExpressionType::Match(matching) => {
+            let value = evaluate_expression(&matching.value, env)?;
+
+            for arm in &matching.arms {
+                let matched = match &arm.pattern {
+                    ast::Pattern::Wildcard | ast::Pattern::Binding(_) => true,
+                    ast::Pattern::Literal(literal) => {
+                        let literal = evaluate_expression(literal, env)?;
+                        matches_literal(&value.value, &literal.value)
+                    }
+                    ast::Pattern::Range(low, high) => {
+                        let low = evaluate_expression(low, env)?;
+                        let high = evaluate_expression(high, env)?;
+                        matches_range(&value, &low, &high, arm.line, arm.column)?
+                    }
+                    ast::Pattern::Type(type_name) => {
+                        let type_name = evaluate_expression(type_name, env)?;
+                        matches_type(value.clone(), type_name, arm.line, arm.column)?
+                    }
                 };
+                if !matched {
+                    continue;
+                }
 
-                new_env.set(arg, args[i].value.clone(), false);
-            }
+                // A binding only lives for its own arm, whatever it shadowed is put back
+                // afterwards so an arm whose guard fails leaves the scope untouched
+                let result = match &arm.pattern {
+                    ast::Pattern::Binding(binding) => match &binding.expression_type {
+                        ast::ExpressionType::Identifier(name) => {
+                            let shadowed = env.variables.insert(name.name, value.value.clone());
+                            let result = evaluate_arm(arm, env);
+                            match shadowed {
+                                Some(shadowed) => env.variables.insert(name.name, shadowed),
+                                None => env.variables.remove(&name.name),
+                            };
+                            result?
+                        }
+                        _ => evaluate_arm(arm, env)?,
+                    },
+                    _ => evaluate_arm(arm, env)?,
+                };
 
-            let mut last_value = RuntimeValue::Null;
-            for expression in &func.declaration.body {
-                last_value = evaluate_expression(expression, &mut new_env)?.value;
-                if let RuntimeValue::Return(value) = last_value {
-                    let v = *value;
-                    return Ok(v);
+                if let Some(result) = result {
+                    return Ok(RuntimeType {
+                        value: result.value,
+                        line: expression.line,
+                        column: expression.column,
+                    });
                 }
             }
 
-            Ok(RuntimeType {
-                value: last_value,
-                line: expression.line,
-                column: expression.column,
-            })
+            Err(UnmatchedValue::new(value, expression.line, expression.column).into())
         }
         ast::ExpressionType::Include(inc) => {
             let path_expr = &*inc.path;
@@ -332,7 +477,7 @@ fn evaluate_expression(
                 }
             };
 
-            let path = path.value.clone();
+            let path = path.value.to_string();
 
             let mut file = std::fs::File::open(path.clone())
                 .map_err(|e| IOError::new(e, path_expr.line, path_expr.column).into())?;
@@ -341,7 +486,8 @@ fn evaluate_expression(
             file.read_to_string(&mut source_code)
                 .map_err(|e| IOError::new(e, path_expr.line, path_expr.column).into())?;
 
-            let ast = Parser::produce_ast(&source_code)?;
+            let mut ast = Parser::produce_ast(&source_code)?;
+            resolve(&mut ast, env)?;
 
             let result = run(&ast, env)?;
 
@@ -362,3 +508,223 @@ fn evaluate_expression(
         }
     }
 }
+
+/// Runs the expressions of a `try` block, stopping at a `return` like an `if` does
+fn evaluate_block(body: &[ast::Expression], env: &mut Environment) -> Result<RuntimeValue, Error> {
+    let mut last_value = RuntimeValue::Null;
+    for expression in body {
+        last_value = evaluate_expression(expression, env)?.value;
+        if let RuntimeValue::Return(_) = last_value {
+            break;
+        }
+    }
+
+    Ok(last_value)
+}
+
+/// Whether a value equals the literal of a `match` arm, values that cannot be
+/// compared to it simply do not match
+pub(crate) fn matches_literal(value: &RuntimeValue, literal: &RuntimeValue) -> bool {
+    match (value, literal) {
+        (RuntimeValue::Null, RuntimeValue::Null) => true,
+        _ => matches!(
+            value.operation(literal, Operator::Relational(Relational::Equal)),
+            Ok(RuntimeValue::Boolean(BooleanVal { value: true }))
+        ),
+    }
+}
+
+/// Evaluates the guard of an arm whose pattern matched and then its body, `None`
+/// when the guard is false
+fn evaluate_arm(arm: &ast::Arm, env: &mut Environment) -> Result<Option<RuntimeType>, Error> {
+    if let Some(guard) = &arm.guard {
+        let condition = evaluate_expression(guard, env)?;
+        match &condition.value {
+            RuntimeValue::Boolean(value) if value.value => {}
+            RuntimeValue::Boolean(_) => return Ok(None),
+            _ => return Err(InvalidCondition::new(condition, guard.line, guard.column).into()),
+        }
+    }
+
+    evaluate_expression(&arm.body, env).map(Some)
+}
+
+/// Whether a value lies within both ends of a range pattern, `low..high`. Like
+/// with a literal a value of another type than the ends does not match, but ends
+/// that cannot be ordered are reported.
+pub(crate) fn matches_range(
+    value: &RuntimeType,
+    low: &RuntimeType,
+    high: &RuntimeType,
+    line: usize,
+    column: usize,
+) -> Result<bool, Error> {
+    for (bound, operator) in [
+        (low, Relational::GreaterThanOrEqual),
+        (high, Relational::LessThanOrEqual),
+    ] {
+        let operator = Operator::Relational(operator);
+
+        match value.value.operation(&bound.value, operator.clone()) {
+            Ok(RuntimeValue::Boolean(within)) if within.value => {}
+            Ok(_) | Err(BinaryOperationError::InvalidOperationType) => return Ok(false),
+            Err(error) => {
+                return Err(operation_error(
+                    error,
+                    value.clone(),
+                    bound.clone(),
+                    operator,
+                    line,
+                    column,
+                ))
+            }
+        }
+    }
+
+    Ok(true)
+}
+
+/// Checks a value against the type of an `is` pattern like the `is` operator does
+pub(crate) fn matches_type(
+    value: RuntimeType,
+    type_name: RuntimeType,
+    line: usize,
+    column: usize,
+) -> Result<bool, Error> {
+    let result = binary_operation(
+        value,
+        type_name,
+        &Operator::Relational(Relational::Is),
+        line,
+        column,
+    )?;
+
+    Ok(matches!(
+        result.value,
+        RuntimeValue::Boolean(BooleanVal { value: true })
+    ))
+}
+
+/// Reads a member of an evaluated value, the result takes the given position
+pub(crate) fn member_access(
+    object: RuntimeType,
+    name: Symbol,
+    optional: bool,
+    line: usize,
+    column: usize,
+) -> Result<RuntimeType, Error> {
+    let value = match &object.value {
+        RuntimeValue::Null if optional => Some(RuntimeValue::Null),
+        RuntimeValue::Error(error) => error.field(name.as_str()),
+        _ => None,
+    };
+
+    match value {
+        Some(value) => Ok(RuntimeType {
+            value,
+            line,
+            column,
+        }),
+        None => Err(UndefinedMember::new(name.to_string(), object, line, column).into()),
+    }
+}
+
+/// Applies a binary operator to evaluated operands, the result takes the given position
+pub(crate) fn binary_operation(
+    left: RuntimeType,
+    right: RuntimeType,
+    operator: &Operator,
+    line: usize,
+    column: usize,
+) -> Result<RuntimeType, Error> {
+    match left.value.operation(&right.value, operator.clone()) {
+        Ok(value) => Ok(RuntimeType {
+            value,
+            line,
+            column,
+        }),
+        Err(error) => Err(operation_error(
+            error,
+            left,
+            right,
+            operator.clone(),
+            line,
+            column,
+        )),
+    }
+}
+
+/// The error a failed binary operation on `left` and `right` is reported with
+fn operation_error(
+    error: BinaryOperationError,
+    left: RuntimeType,
+    right: RuntimeType,
+    operator: Operator,
+    line: usize,
+    column: usize,
+) -> Error {
+    match error {
+        BinaryOperationError::InvalidOperation => {
+            InvalidOperation::new(left, right, operator).into()
+        }
+        BinaryOperationError::InvalidOperationType => {
+            InvalidOperationType::new(left, Some(right), operator).into()
+        }
+        BinaryOperationError::InvalidOperator => {
+            InvalidOperator::new(operator, line, column).into()
+        }
+        BinaryOperationError::CharacterOutOfRange(value) => {
+            CharacterOutOfRange::new(value, line, column).into()
+        }
+    }
+}
+
+/// Applies a unary operator to an evaluated operand, the result takes the given position
+pub(crate) fn unary_operation(
+    raw_value: RuntimeType,
+    operator: &Operator,
+    line: usize,
+    column: usize,
+) -> Result<RuntimeType, Error> {
+    // ensure value is a number
+    let value = match &raw_value.value {
+        RuntimeValue::Number(value) => Some(value),
+        _ => None,
+    };
+
+    if value.is_some() {
+        let value = value.unwrap();
+        return match operator {
+            Operator::Arithmatic(Arithmatic::Minus) => Ok(RuntimeType {
+                value: RuntimeValue::Number((-value.value).into()),
+                line,
+                column,
+            }),
+            Operator::Arithmatic(Arithmatic::Plus) => Ok(RuntimeType {
+                value: RuntimeValue::Number((value.value).into()),
+                line,
+                column,
+            }),
+            _ => Err(InvalidOperator::new(operator.clone(), line, column).into()),
+        };
+    }
+
+    let value = match &raw_value.value {
+        RuntimeValue::Boolean(value) => Some(value),
+        _ => None,
+    };
+
+    if value.is_some() {
+        let value = value.unwrap();
+        return match operator {
+            Operator::Logical(Logical::Not) => Ok(RuntimeType {
+                value: RuntimeValue::Boolean((!value.value).into()),
+                line,
+                column,
+            }),
+            _ => Err(InvalidOperator::new(operator.clone(), line, column).into()),
+        };
+    }
+
+    Err(InvalidOperationType::new(raw_value, None, operator.clone()).into())
+}
@@ -0,0 +1,314 @@
+use std::{collections::HashSet, io::Read};
+
+use crate::{
+    errors::*,
+    parser::{
+        ast::{self, ExpressionType},
+        Parser,
+    },
+    symbol::Symbol,
+};
+
+use super::Environment;
+
+/// Checks that every variable and function the program uses is declared before it
+/// runs, and records in each identifier how many scopes up its value lives.
+///
+/// Names already in `env` are treated as declared, which lets the REPL and
+/// included files be resolved against the environment they run in.
+pub fn resolve(program: &mut ast::Program, env: &Environment) -> Result<(), Error> {
+    let mut environments = vec![env];
+    while let Some(parent) = environments.last().unwrap().parent() {
+        environments.push(parent);
+    }
+
+    let mut resolver = Resolver {
+        scopes: environments
+            .into_iter()
+            .rev()
+            .map(|env| Scope {
+                declared: env.iter().map(|(name, _)| *name).collect(),
+                ..Default::default()
+            })
+            .collect(),
+        branches: 0,
+        trying: 0,
+        including: Vec::new(),
+    };
+
+    for expression in &mut program.body {
+        resolver.expression(expression)?;
+    }
+
+    Ok(())
+}
+
+// Mirrors the environments the interpreter creates: one for the program and one
+// for each function call, `if` blocks and included files share the current one.
+#[derive(Default)]
+struct Scope {
+    declared: HashSet<Symbol>,
+    // Declared inside an `if` or a `try`, so it may or may not exist at runtime
+    maybe_declared: HashSet<Symbol>,
+    // An include we could not read may have declared anything
+    dynamic: bool,
+}
+
+enum Resolution {
+    Depth(usize),
+    Unknown,
+    Unresolved,
+}
+
+struct Resolver {
+    scopes: Vec<Scope>,
+    // How many `if` and `try` blocks deep we are in the current function
+    branches: usize,
+    // How many `try` bodies deep we are, undefined names there are left for the
+    // `catch` to handle when they are reached
+    trying: usize,
+    // Files currently being included, to not follow circular includes
+    including: Vec<String>,
+}
+
+impl Resolver {
+    fn lookup(&self, name: Symbol) -> Resolution {
+        let mut certain = true;
+
+        for (depth, scope) in self.scopes.iter().rev().enumerate() {
+            if scope.declared.contains(&name) {
+                return if certain {
+                    Resolution::Depth(depth)
+                } else {
+                    Resolution::Unknown
+                };
+            }
+
+            if scope.dynamic || scope.maybe_declared.contains(&name) {
+                certain = false;
+            }
+        }
+
+        if certain {
+            Resolution::Unresolved
+        } else {
+            Resolution::Unknown
+        }
+    }
+
+    fn declare(&mut self, name: Symbol) {
+        let branches = self.branches;
+        let scope = self.scopes.last_mut().unwrap();
+
+        if branches == 0 {
+            scope.declared.insert(name);
+        } else if !scope.declared.contains(&name) {
+            scope.maybe_declared.insert(name);
+        }
+    }
+
+    fn block(&mut self, body: &mut [ast::Expression]) -> Result<(), Error> {
+        for expression in body {
+            self.expression(expression)?;
+        }
+
+        Ok(())
+    }
+
+    fn expression(&mut self, expression: &mut ast::Expression) -> Result<(), Error> {
+        let (line, column) = (expression.line, expression.column);
+
+        match &mut expression.expression_type {
+            ExpressionType::Identifier(identifier) => match self.lookup(identifier.name) {
+                Resolution::Depth(depth) => identifier.depth = Some(depth),
+                Resolution::Unknown => identifier.depth = None,
+                Resolution::Unresolved if self.trying > 0 => identifier.depth = None,
+                Resolution::Unresolved => {
+                    return Err(
+                        UndefinedVariable::new(identifier.name.to_string(), line, column).into(),
+                    )
+                }
+            },
+            ExpressionType::Interpolation(interpolation) => {
+                for part in &mut interpolation.parts {
+                    if let ast::InterpolationPart::Expression(part) = part {
+                        self.expression(part)?;
+                    }
+                }
+            }
+            ExpressionType::Number(_)
+            | ExpressionType::String(_)
+            | ExpressionType::Character(_)
+            | ExpressionType::Boolean(_)
+            | ExpressionType::Null
+            | ExpressionType::Type(_) => {}
+            ExpressionType::Assignment(assignment) => {
+                self.expression(&mut assignment.right)?;
+
+                if let ExpressionType::Identifier(identifier) = &assignment.left.expression_type {
+                    self.declare(identifier.name);
+                }
+            }
+            ExpressionType::Binary(binary) => {
+                self.expression(&mut binary.left)?;
+                self.expression(&mut binary.right)?;
+            }
+            ExpressionType::Unary(unary) => self.expression(&mut unary.right)?,
+            ExpressionType::Grouping(group) => self.expression(&mut group.expression)?,
+            ExpressionType::If(cond) => {
+                self.expression(&mut cond.condition)?;
+
+                self.branches += 1;
+                self.block(&mut cond.body)?;
+                if let Some(else_body) = &mut cond.else_body {
+                    self.block(else_body)?;
+                }
+                self.branches -= 1;
+            }
+            ExpressionType::Match(matching) => {
+                self.expression(&mut matching.value)?;
+
+                // Any one of the arms may run, or none of them
+                self.branches += 1;
+                for arm in &mut matching.arms {
+                    let mut binding = None;
+                    match &mut arm.pattern {
+                        ast::Pattern::Wildcard => {}
+                        ast::Pattern::Literal(literal) => self.expression(literal)?,
+                        ast::Pattern::Range(low, high) => {
+                            self.expression(low)?;
+                            self.expression(high)?;
+                        }
+                        ast::Pattern::Type(type_name) => self.expression(type_name)?,
+                        ast::Pattern::Binding(pattern) => {
+                            if let ExpressionType::Identifier(identifier) = &pattern.expression_type
+                            {
+                                binding = Some(identifier.name);
+                            }
+                        }
+                    }
+
+                    // The binding is only visible to the guard and body of its own arm
+                    let scope = self.scopes.last_mut().unwrap();
+                    let binding = binding.filter(|name| scope.declared.insert(*name));
+
+                    if let Some(guard) = &mut arm.guard {
+                        self.expression(guard)?;
+                    }
+                    self.expression(&mut arm.body)?;
+
+                    if let Some(name) = binding {
+                        self.scopes.last_mut().unwrap().declared.remove(&name);
+                    }
+                }
+                self.branches -= 1;
+            }
+            ExpressionType::Function(func) => {
+                // Calls run in a fresh scope holding the parameters, on top of a copy of
+                // what was declared before the function, which does not include itself
+                let mut scope = Scope::default();
+                for parameter in &func.parameters {
+                    if let ExpressionType::Identifier(identifier) = &parameter.expression_type {
+                        scope.declared.insert(identifier.name);
+                    }
+                }
+
+                let branches = std::mem::replace(&mut self.branches, 0);
+                let trying = std::mem::replace(&mut self.trying, 0);
+                self.scopes.push(scope);
+
+                let result = self.block(&mut func.body);
+
+                self.scopes.pop();
+                self.branches = branches;
+                self.trying = trying;
+                result?;
+
+                if let ExpressionType::Identifier(identifier) = &func.name.expression_type {
+                    self.declare(identifier.name);
+                }
+            }
+            ExpressionType::Return(ret) => self.expression(&mut ret.value)?,
+            ExpressionType::Call(call) => {
+                if let ExpressionType::Identifier(identifier) = &mut call.name.expression_type {
+                    match self.lookup(identifier.name) {
+                        Resolution::Depth(depth) => identifier.depth = Some(depth),
+                        Resolution::Unknown => identifier.depth = None,
+                        Resolution::Unresolved if self.trying > 0 => identifier.depth = None,
+                        Resolution::Unresolved => {
+                            return Err(UndefinedFunction::new(
+                                identifier.name.to_string(),
+                                call.name.line,
+                                call.name.column,
+                            )
+                            .into())
+                        }
+                    }
+                }
+
+                self.block(&mut call.parameters)?;
+            }
+            ExpressionType::Member(member) => self.expression(&mut member.object)?,
+            ExpressionType::Throw(throw) => self.expression(&mut throw.value)?,
+            ExpressionType::Try(try_block) => {
+                // The body may stop at any point and the catch may not run at all,
+                // the finally always runs
+                self.branches += 1;
+                self.trying += 1;
+                let result = self.block(&mut try_block.body);
+                self.trying -= 1;
+                result?;
+                if let Some(catch) = &mut try_block.catch {
+                    if let ExpressionType::Identifier(identifier) = &catch.name.expression_type {
+                        self.declare(identifier.name);
+                    }
+                    self.block(&mut catch.body)?;
+                }
+                self.branches -= 1;
+
+                if let Some(finally) = &mut try_block.finally {
+                    self.block(finally)?;
+                }
+            }
+            ExpressionType::Include(include) => {
+                self.expression(&mut include.path)?;
+
+                let included = match &include.path.expression_type {
+                    ExpressionType::String(path) => self.include(path)?,
+                    _ => false,
+                };
+
+                if !included {
+                    self.scopes.last_mut().unwrap().dynamic = true;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Resolves an included file in the current scope, so the names it declares
+    /// are known afterwards. Returns false when the file could not be read.
+    fn include(&mut self, path: &str) -> Result<bool, Error> {
+        if self.including.iter().any(|including| including == path) {
+            return Ok(false);
+        }
+
+        let mut source = String::new();
+        let read = std::fs::File::open(path).and_then(|mut file| file.read_to_string(&mut source));
+        if read.is_err() {
+            return Ok(false);
+        }
+
+        let mut program = match Parser::produce_ast(&source) {
+            Ok(program) => program,
+            Err(_) => return Ok(false),
+        };
+
+        self.including.push(path.to_string());
+        let result = self.block(&mut program.body);
+        self.including.pop();
+
+        result.map(|_| true)
+    }
+}
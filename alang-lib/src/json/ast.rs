@@ -0,0 +1,430 @@
+use crate::{
+    errors::Error,
+    parser::ast::{
+        Arm, Assignment, Binary, Call, Catch, Expression, ExpressionType, Function, Grouping,
+        Identifier, If, Include, Interpolation, InterpolationPart, Match, Member, Pattern, Program,
+        Return, Throw, Try, Unary,
+    },
+    symbol::Symbol,
+    types::{Arithmatic, Logical, Operator, Relational},
+};
+
+use super::{invalid, FromJson, Json, ToJson};
+
+impl ToJson for Program {
+    fn to_json(&self) -> Json {
+        Json::object(vec![
+            ("type", Json::from("Program")),
+            ("body", self.body.to_json()),
+        ])
+    }
+}
+
+impl FromJson for Program {
+    fn from_json(json: &Json) -> Result<Self, Error> {
+        Ok(Program {
+            body: FromJson::from_json(json.field("body")?)?,
+        })
+    }
+}
+
+// Every expression is an object with its "type", "line" and "column",
+// followed by the fields of the node
+impl ToJson for Expression {
+    fn to_json(&self) -> Json {
+        let (name, mut fields) = match &self.expression_type {
+            ExpressionType::Identifier(identifier) => (
+                "Identifier",
+                vec![
+                    ("name", Json::from(identifier.name.as_str())),
+                    ("depth", Json::from(identifier.depth)),
+                ],
+            ),
+            ExpressionType::Number(value) => ("Number", vec![("value", Json::from(*value))]),
+            ExpressionType::String(value) => {
+                ("String", vec![("value", Json::from(value.as_str()))])
+            }
+            ExpressionType::Interpolation(interpolation) => (
+                "Interpolation",
+                vec![("parts", interpolation.parts.to_json())],
+            ),
+            ExpressionType::Character(value) => {
+                ("Character", vec![("value", Json::from(value.to_string()))])
+            }
+            ExpressionType::Boolean(value) => ("Boolean", vec![("value", Json::from(*value))]),
+            ExpressionType::Null => ("Null", vec![]),
+            ExpressionType::Type(name) => ("Type", vec![("name", Json::from(name.as_str()))]),
+            ExpressionType::Assignment(assignment) => (
+                "Assignment",
+                vec![
+                    ("left", assignment.left.to_json()),
+                    ("right", assignment.right.to_json()),
+                    ("doc", Json::from(assignment.doc.as_deref())),
+                ],
+            ),
+            ExpressionType::Binary(binary) => (
+                "Binary",
+                vec![
+                    ("left", binary.left.to_json()),
+                    ("operator", Json::from(binary.operator.to_string())),
+                    ("right", binary.right.to_json()),
+                ],
+            ),
+            ExpressionType::Unary(unary) => (
+                "Unary",
+                vec![
+                    ("operator", Json::from(unary.operator.to_string())),
+                    ("right", unary.right.to_json()),
+                ],
+            ),
+            ExpressionType::Grouping(grouping) => (
+                "Grouping",
+                vec![("expression", grouping.expression.to_json())],
+            ),
+            ExpressionType::If(cond) => (
+                "If",
+                vec![
+                    ("condition", cond.condition.to_json()),
+                    ("body", cond.body.to_json()),
+                    (
+                        "else_body",
+                        match &cond.else_body {
+                            Some(else_body) => else_body.to_json(),
+                            None => Json::Null,
+                        },
+                    ),
+                    ("else_line", Json::from(cond.else_line)),
+                    ("end_line", Json::from(cond.end_line)),
+                ],
+            ),
+            ExpressionType::Function(function) => (
+                "Function",
+                vec![
+                    ("name", function.name.to_json()),
+                    ("parameters", function.parameters.to_json()),
+                    ("body", function.body.to_json()),
+                    ("end_line", Json::from(function.end_line)),
+                    ("doc", Json::from(function.doc.as_deref())),
+                ],
+            ),
+            ExpressionType::Return(ret) => ("Return", vec![("value", ret.value.to_json())]),
+            ExpressionType::Call(call) => (
+                "Call",
+                vec![
+                    ("name", call.name.to_json()),
+                    ("parameters", call.parameters.to_json()),
+                    ("optional", Json::from(call.optional)),
+                ],
+            ),
+            ExpressionType::Member(member) => (
+                "Member",
+                vec![
+                    ("object", member.object.to_json()),
+                    ("name", Json::from(member.name.as_str())),
+                    ("optional", Json::from(member.optional)),
+                ],
+            ),
+            ExpressionType::Throw(throw) => ("Throw", vec![("value", throw.value.to_json())]),
+            ExpressionType::Try(try_block) => (
+                "Try",
+                vec![
+                    ("body", try_block.body.to_json()),
+                    (
+                        "catch",
+                        match &try_block.catch {
+                            Some(catch) => Json::object(vec![
+                                ("name", catch.name.to_json()),
+                                ("body", catch.body.to_json()),
+                                ("line", Json::from(catch.line)),
+                            ]),
+                            None => Json::Null,
+                        },
+                    ),
+                    (
+                        "finally",
+                        match &try_block.finally {
+                            Some(finally) => finally.to_json(),
+                            None => Json::Null,
+                        },
+                    ),
+                    ("finally_line", Json::from(try_block.finally_line)),
+                    ("end_line", Json::from(try_block.end_line)),
+                ],
+            ),
+            ExpressionType::Match(matching) => (
+                "Match",
+                vec![
+                    ("value", matching.value.to_json()),
+                    ("arms", matching.arms.to_json()),
+                    ("end_line", Json::from(matching.end_line)),
+                ],
+            ),
+            ExpressionType::Include(include) => ("Include", vec![("path", include.path.to_json())]),
+        };
+
+        fields.insert(0, ("type", Json::from(name)));
+        fields.insert(1, ("line", Json::from(self.line)));
+        fields.insert(2, ("column", Json::from(self.column)));
+
+        Json::object(fields)
+    }
+}
+
+impl FromJson for Expression {
+    fn from_json(json: &Json) -> Result<Self, Error> {
+        let name = json.field("type")?.as_str()?;
+
+        let expression_type = match name {
+            "Identifier" => ExpressionType::Identifier(Identifier {
+                name: Symbol::intern(json.field("name")?.as_str()?),
+                depth: match json.get("depth") {
+                    None | Some(Json::Null) => None,
+                    Some(depth) => Some(depth.as_usize()?),
+                },
+            }),
+            "Number" => ExpressionType::Number(json.field("value")?.as_f64()?),
+            "String" => ExpressionType::String(json.field("value")?.as_str()?.to_string()),
+            "Interpolation" => ExpressionType::Interpolation(Interpolation {
+                parts: FromJson::from_json(json.field("parts")?)?,
+            }),
+            "Character" => {
+                let value = json.field("value")?.as_str()?;
+                let mut chars = value.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => ExpressionType::Character(c),
+                    _ => return Err(invalid(format!("invalid character '{}'", value))),
+                }
+            }
+            "Boolean" => ExpressionType::Boolean(json.field("value")?.as_bool()?),
+            "Null" => ExpressionType::Null,
+            "Type" => ExpressionType::Type(json.field("name")?.as_str()?.to_string()),
+            "Assignment" => ExpressionType::Assignment(Assignment {
+                left: expression(json, "left")?,
+                right: expression(json, "right")?,
+                doc: doc(json)?,
+            }),
+            "Binary" => ExpressionType::Binary(Binary {
+                left: expression(json, "left")?,
+                operator: operator(json)?,
+                right: expression(json, "right")?,
+            }),
+            "Unary" => ExpressionType::Unary(Unary {
+                operator: operator(json)?,
+                right: expression(json, "right")?,
+            }),
+            "Grouping" => ExpressionType::Grouping(Grouping {
+                expression: expression(json, "expression")?,
+            }),
+            "If" => ExpressionType::If(If {
+                condition: expression(json, "condition")?,
+                body: FromJson::from_json(json.field("body")?)?,
+                else_body: match json.get("else_body") {
+                    None | Some(Json::Null) => None,
+                    Some(else_body) => Some(FromJson::from_json(else_body)?),
+                },
+                else_line: match json.get("else_line") {
+                    None | Some(Json::Null) => None,
+                    Some(line) => Some(line.as_usize()?),
+                },
+                end_line: json.field("end_line")?.as_usize()?,
+            }),
+            "Function" => ExpressionType::Function(Function {
+                name: expression(json, "name")?,
+                parameters: FromJson::from_json(json.field("parameters")?)?,
+                body: FromJson::from_json(json.field("body")?)?,
+                end_line: json.field("end_line")?.as_usize()?,
+                doc: doc(json)?,
+            }),
+            "Return" => ExpressionType::Return(Return {
+                value: expression(json, "value")?,
+            }),
+            "Call" => ExpressionType::Call(Call {
+                name: expression(json, "name")?,
+                parameters: FromJson::from_json(json.field("parameters")?)?,
+                optional: match json.get("optional") {
+                    None | Some(Json::Null) => false,
+                    Some(optional) => optional.as_bool()?,
+                },
+            }),
+            "Member" => ExpressionType::Member(Member {
+                object: expression(json, "object")?,
+                name: Symbol::intern(json.field("name")?.as_str()?),
+                optional: json.field("optional")?.as_bool()?,
+            }),
+            "Throw" => ExpressionType::Throw(Throw {
+                value: expression(json, "value")?,
+            }),
+            "Try" => ExpressionType::Try(Try {
+                body: FromJson::from_json(json.field("body")?)?,
+                catch: match json.get("catch") {
+                    None | Some(Json::Null) => None,
+                    Some(catch) => Some(Catch {
+                        name: expression(catch, "name")?,
+                        body: FromJson::from_json(catch.field("body")?)?,
+                        line: catch.field("line")?.as_usize()?,
+                    }),
+                },
+                finally: match json.get("finally") {
+                    None | Some(Json::Null) => None,
+                    Some(finally) => Some(FromJson::from_json(finally)?),
+                },
+                finally_line: match json.get("finally_line") {
+                    None | Some(Json::Null) => None,
+                    Some(line) => Some(line.as_usize()?),
+                },
+                end_line: json.field("end_line")?.as_usize()?,
+            }),
+            "Match" => ExpressionType::Match(Match {
+                value: expression(json, "value")?,
+                arms: FromJson::from_json(json.field("arms")?)?,
+                end_line: json.field("end_line")?.as_usize()?,
+            }),
+            "Include" => ExpressionType::Include(Include {
+                path: expression(json, "path")?,
+            }),
+            _ => return Err(invalid(format!("unknown expression type '{}'", name))),
+        };
+
+        Ok(Expression::new(
+            expression_type,
+            json.field("line")?.as_usize()?,
+            json.field("column")?.as_usize()?,
+        ))
+    }
+}
+
+// Text is a string and an embedded expression an object
+impl ToJson for InterpolationPart {
+    fn to_json(&self) -> Json {
+        match self {
+            InterpolationPart::Text(text) => Json::from(text.as_str()),
+            InterpolationPart::Expression(expression) => expression.to_json(),
+        }
+    }
+}
+
+impl FromJson for InterpolationPart {
+    fn from_json(json: &Json) -> Result<Self, Error> {
+        match json {
+            Json::String(text) => Ok(InterpolationPart::Text(text.clone())),
+            _ => Ok(InterpolationPart::Expression(Expression::from_json(json)?)),
+        }
+    }
+}
+
+impl ToJson for Arm {
+    fn to_json(&self) -> Json {
+        Json::object(vec![
+            ("pattern", self.pattern.to_json()),
+            (
+                "guard",
+                match &self.guard {
+                    Some(guard) => guard.to_json(),
+                    None => Json::Null,
+                },
+            ),
+            ("body", self.body.to_json()),
+            ("line", Json::from(self.line)),
+            ("column", Json::from(self.column)),
+        ])
+    }
+}
+
+impl FromJson for Arm {
+    fn from_json(json: &Json) -> Result<Self, Error> {
+        Ok(Arm {
+            pattern: Pattern::from_json(json.field("pattern")?)?,
+            guard: match json.get("guard") {
+                None | Some(Json::Null) => None,
+                Some(guard) => Some(Box::new(Expression::from_json(guard)?)),
+            },
+            body: expression(json, "body")?,
+            line: json.field("line")?.as_usize()?,
+            column: json.field("column")?.as_usize()?,
+        })
+    }
+}
+
+// An object with the "kind" of pattern and the expressions it holds
+impl ToJson for Pattern {
+    fn to_json(&self) -> Json {
+        match self {
+            Pattern::Wildcard => Json::object(vec![("kind", Json::from("Wildcard"))]),
+            Pattern::Literal(value) => Json::object(vec![
+                ("kind", Json::from("Literal")),
+                ("value", value.to_json()),
+            ]),
+            Pattern::Range(low, high) => Json::object(vec![
+                ("kind", Json::from("Range")),
+                ("low", low.to_json()),
+                ("high", high.to_json()),
+            ]),
+            Pattern::Type(value) => Json::object(vec![
+                ("kind", Json::from("Type")),
+                ("value", value.to_json()),
+            ]),
+            Pattern::Binding(name) => Json::object(vec![
+                ("kind", Json::from("Binding")),
+                ("name", name.to_json()),
+            ]),
+        }
+    }
+}
+
+impl FromJson for Pattern {
+    fn from_json(json: &Json) -> Result<Self, Error> {
+        let kind = json.field("kind")?.as_str()?;
+
+        match kind {
+            "Wildcard" => Ok(Pattern::Wildcard),
+            "Literal" => Ok(Pattern::Literal(expression(json, "value")?)),
+            "Range" => Ok(Pattern::Range(
+                expression(json, "low")?,
+                expression(json, "high")?,
+            )),
+            "Type" => Ok(Pattern::Type(expression(json, "value")?)),
+            "Binding" => Ok(Pattern::Binding(expression(json, "name")?)),
+            _ => Err(invalid(format!("unknown pattern kind '{}'", kind))),
+        }
+    }
+}
+
+fn expression(json: &Json, key: &str) -> Result<Box<Expression>, Error> {
+    Ok(Box::new(Expression::from_json(json.field(key)?)?))
+}
+
+/// Reads the optional "doc" field of declarations
+fn doc(json: &Json) -> Result<Option<String>, Error> {
+    match json.get("doc") {
+        None | Some(Json::Null) => Ok(None),
+        Some(doc) => Ok(Some(doc.as_str()?.to_string())),
+    }
+}
+
+/// Reads the "operator" field, written the way it appears in the source
+fn operator(json: &Json) -> Result<Operator, Error> {
+    let symbol = json.field("operator")?.as_str()?;
+
+    let operator = match symbol {
+        "+" => Operator::Arithmatic(Arithmatic::Plus),
+        "-" => Operator::Arithmatic(Arithmatic::Minus),
+        "*" => Operator::Arithmatic(Arithmatic::Multiply),
+        "/" => Operator::Arithmatic(Arithmatic::Divide),
+        "%" => Operator::Arithmatic(Arithmatic::Modulo),
+        "^" => Operator::Arithmatic(Arithmatic::Exponentiation),
+        "==" => Operator::Relational(Relational::Equal),
+        "!=" => Operator::Relational(Relational::NotEqual),
+        "<" => Operator::Relational(Relational::LessThan),
+        ">" => Operator::Relational(Relational::GreaterThan),
+        "<=" => Operator::Relational(Relational::LessThanOrEqual),
+        ">=" => Operator::Relational(Relational::GreaterThanOrEqual),
+        "is" => Operator::Relational(Relational::Is),
+        "&&" => Operator::Logical(Logical::And),
+        "||" => Operator::Logical(Logical::Or),
+        "??" => Operator::Logical(Logical::Coalesce),
+        "!" => Operator::Logical(Logical::Not),
+        _ => return Err(invalid(format!("unknown operator '{}'", symbol))),
+    };
+
+    Ok(operator)
+}
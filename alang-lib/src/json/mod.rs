@@ -0,0 +1,258 @@
+//! A small JSON implementation, used to exchange tokens and syntax trees with
+//! other tools.
+
+mod ast;
+mod reader;
+mod token;
+
+use std::fmt::{Display, Write};
+
+use crate::errors::{Error, InvalidJson};
+
+pub use reader::parse;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Boolean(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    /// Keys keep the order they were inserted in
+    Object(Vec<(String, Json)>),
+}
+
+pub trait ToJson {
+    fn to_json(&self) -> Json;
+}
+
+pub trait FromJson: Sized {
+    fn from_json(json: &Json) -> Result<Self, Error>;
+}
+
+impl Json {
+    pub fn object(fields: Vec<(&str, Json)>) -> Self {
+        Json::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields
+                .iter()
+                .find(|(field, _)| field == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// Like `get`, but a missing key is an error
+    pub fn field(&self, key: &str) -> Result<&Json, Error> {
+        self.get(key)
+            .ok_or_else(|| invalid(format!("missing field '{}'", key)))
+    }
+
+    pub fn as_str(&self) -> Result<&str, Error> {
+        match self {
+            Json::String(value) => Ok(value),
+            _ => Err(invalid(format!("expected a string, got {}", self))),
+        }
+    }
+
+    /// Numbers JSON cannot represent are written as the strings "NaN",
+    /// "Infinity" and "-Infinity", which are accepted here as well
+    pub fn as_f64(&self) -> Result<f64, Error> {
+        match self {
+            Json::Number(value) => Ok(*value),
+            Json::String(value) if value == "NaN" => Ok(f64::NAN),
+            Json::String(value) if value == "Infinity" => Ok(f64::INFINITY),
+            Json::String(value) if value == "-Infinity" => Ok(f64::NEG_INFINITY),
+            _ => Err(invalid(format!("expected a number, got {}", self))),
+        }
+    }
+
+    pub fn as_usize(&self) -> Result<usize, Error> {
+        match self {
+            Json::Number(value) if *value >= 0.0 && value.fract() == 0.0 => Ok(*value as usize),
+            _ => Err(invalid(format!(
+                "expected a positive integer, got {}",
+                self
+            ))),
+        }
+    }
+
+    pub fn as_bool(&self) -> Result<bool, Error> {
+        match self {
+            Json::Boolean(value) => Ok(*value),
+            _ => Err(invalid(format!("expected a boolean, got {}", self))),
+        }
+    }
+
+    pub fn as_array(&self) -> Result<&[Json], Error> {
+        match self {
+            Json::Array(values) => Ok(values),
+            _ => Err(invalid(format!("expected an array, got {}", self))),
+        }
+    }
+
+    /// Writes the value over multiple lines, indented by two spaces
+    pub fn pretty(&self) -> String {
+        let mut output = String::new();
+        self.write_pretty(&mut output, 0);
+        output
+    }
+
+    fn write_pretty(&self, output: &mut String, indent: usize) {
+        let padding = "  ".repeat(indent + 1);
+
+        match self {
+            Json::Array(values) if !values.is_empty() => {
+                output.push_str("[\n");
+                for (i, value) in values.iter().enumerate() {
+                    output.push_str(&padding);
+                    value.write_pretty(output, indent + 1);
+                    output.push_str(if i + 1 < values.len() { ",\n" } else { "\n" });
+                }
+                output.push_str(&"  ".repeat(indent));
+                output.push(']');
+            }
+            Json::Object(fields) if !fields.is_empty() => {
+                output.push_str("{\n");
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    output.push_str(&padding);
+                    write_string(output, key);
+                    output.push_str(": ");
+                    value.write_pretty(output, indent + 1);
+                    output.push_str(if i + 1 < fields.len() { ",\n" } else { "\n" });
+                }
+                output.push_str(&"  ".repeat(indent));
+                output.push('}');
+            }
+            _ => output.push_str(&self.to_string()),
+        }
+    }
+}
+
+impl From<f64> for Json {
+    fn from(value: f64) -> Self {
+        if value.is_nan() {
+            Json::String("NaN".to_string())
+        } else if value.is_infinite() && value > 0.0 {
+            Json::String("Infinity".to_string())
+        } else if value.is_infinite() {
+            Json::String("-Infinity".to_string())
+        } else {
+            Json::Number(value)
+        }
+    }
+}
+
+impl From<usize> for Json {
+    fn from(value: usize) -> Self {
+        Json::Number(value as f64)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(value: &str) -> Self {
+        Json::String(value.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(value: String) -> Self {
+        Json::String(value)
+    }
+}
+
+impl From<bool> for Json {
+    fn from(value: bool) -> Self {
+        Json::Boolean(value)
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Self {
+        match value {
+            Some(value) => value.into(),
+            None => Json::Null,
+        }
+    }
+}
+
+impl<T: ToJson> ToJson for [T] {
+    fn to_json(&self) -> Json {
+        Json::Array(self.iter().map(ToJson::to_json).collect())
+    }
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+    fn from_json(json: &Json) -> Result<Self, Error> {
+        json.as_array()?.iter().map(T::from_json).collect()
+    }
+}
+
+/// Compact output, without any whitespace
+impl Display for Json {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Boolean(value) => write!(f, "{}", value),
+            // Written as strings, like `From<f64>` does, bare `inf` would not be JSON
+            Json::Number(value) if !value.is_finite() => write!(f, "{}", Json::from(*value)),
+            Json::Number(value) => write!(f, "{}", value),
+            Json::String(value) => {
+                let mut output = String::new();
+                write_string(&mut output, value);
+                write!(f, "{}", output)
+            }
+            Json::Array(values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(fields) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}:{}", Json::from(key.as_str()), value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_string(output: &mut String, value: &str) {
+    output.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if c.is_control() => {
+                write!(output, "\\u{:04x}", c as u32).unwrap();
+            }
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+}
+
+/// An error in the structure of otherwise valid JSON
+fn invalid(reason: String) -> Error {
+    InvalidJson::new(reason, 0, 0).into()
+}
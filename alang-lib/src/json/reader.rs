@@ -0,0 +1,232 @@
+use std::{iter::Peekable, str::Chars};
+
+use crate::errors::{Error, InvalidJson};
+
+use super::Json;
+
+/// How deep arrays and objects may be nested, the reader recurses for each level
+/// and would overflow the stack on hostile input long before running out of it
+const MAX_DEPTH: usize = 512;
+
+/// Parses a JSON document
+pub fn parse(source: &str) -> Result<Json, Error> {
+    let mut reader = Reader {
+        chars: source.chars().peekable(),
+        line: 1,
+        column: 1,
+        depth: 0,
+    };
+
+    let value = reader.value()?;
+
+    reader.skip_whitespace();
+    match reader.chars.peek().copied() {
+        Some(c) => Err(reader.error(format!("unexpected '{}' after the value", c))),
+        None => Ok(value),
+    }
+}
+
+struct Reader<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+    column: usize,
+    // Arrays and objects currently open
+    depth: usize,
+}
+
+impl Reader<'_> {
+    fn error(&self, reason: String) -> Error {
+        InvalidJson::new(reason, self.line, self.column).into()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+
+        Some(c)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), Error> {
+        match self.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(self.error(format!("expected '{}', got '{}'", expected, c))),
+            None => Err(self.error(format!("expected '{}', got the end of input", expected))),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(' ' | '\t' | '\n' | '\r') = self.chars.peek() {
+            self.next();
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, Error> {
+        self.skip_whitespace();
+
+        let value = match self.chars.peek().copied() {
+            Some('n') => self.keyword("null", Json::Null)?,
+            Some('t') => self.keyword("true", Json::Boolean(true))?,
+            Some('f') => self.keyword("false", Json::Boolean(false))?,
+            Some('"') => Json::String(self.string()?),
+            Some('[') => self.nested(Self::array)?,
+            Some('{') => self.nested(Self::object)?,
+            Some('-' | '0'..='9') => self.number()?,
+            Some(c) => return Err(self.error(format!("unexpected '{}'", c))),
+            None => return Err(self.error("unexpected end of input".to_string())),
+        };
+
+        self.skip_whitespace();
+
+        Ok(value)
+    }
+
+    fn nested(&mut self, read: fn(&mut Self) -> Result<Json, Error>) -> Result<Json, Error> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error(format!("nested deeper than {} levels", MAX_DEPTH)));
+        }
+
+        self.depth += 1;
+        let value = read(self);
+        self.depth -= 1;
+
+        value
+    }
+
+    fn keyword(&mut self, keyword: &str, value: Json) -> Result<Json, Error> {
+        for expected in keyword.chars() {
+            self.expect(expected)?;
+        }
+
+        Ok(value)
+    }
+
+    fn number(&mut self) -> Result<Json, Error> {
+        let mut number = String::new();
+
+        while let Some(c @ ('-' | '+' | '.' | 'e' | 'E' | '0'..='9')) = self.chars.peek() {
+            number.push(*c);
+            self.next();
+        }
+
+        // Numbers too large for a f64 would parse as infinity, which JSON cannot hold
+        number
+            .parse()
+            .ok()
+            .filter(|value: &f64| value.is_finite())
+            .map(Json::Number)
+            .ok_or_else(|| self.error(format!("invalid number '{}'", number)))
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        self.expect('"')?;
+
+        let mut string = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(string),
+                Some('\\') => string.push(self.escape()?),
+                Some(c) => string.push(c),
+                None => return Err(self.error("unterminated string".to_string())),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, Error> {
+        let c = match self.next() {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('/') => '/',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => {
+                let high = self.hex()?;
+
+                // Characters outside the basic plane are written as a surrogate pair
+                let code = if (0xD800..0xDC00).contains(&high) {
+                    self.expect('\\')?;
+                    self.expect('u')?;
+                    let low = self.hex()?;
+                    0x10000 + ((high - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF)
+                } else {
+                    high
+                };
+
+                char::from_u32(code)
+                    .ok_or_else(|| self.error(format!("invalid character code {:x}", code)))?
+            }
+            Some(c) => return Err(self.error(format!("invalid escape '\\{}'", c))),
+            None => return Err(self.error("unterminated string".to_string())),
+        };
+
+        Ok(c)
+    }
+
+    fn hex(&mut self) -> Result<u32, Error> {
+        let mut code = 0;
+
+        for _ in 0..4 {
+            let digit = self
+                .next()
+                .and_then(|c| c.to_digit(16))
+                .ok_or_else(|| self.error("expected four hex digits after \\u".to_string()))?;
+            code = code * 16 + digit;
+        }
+
+        Ok(code)
+    }
+
+    fn array(&mut self) -> Result<Json, Error> {
+        self.expect('[')?;
+        self.skip_whitespace();
+
+        let mut values = Vec::new();
+        if self.chars.peek() == Some(&']') {
+            self.next();
+            return Ok(Json::Array(values));
+        }
+
+        loop {
+            values.push(self.value()?);
+
+            match self.next() {
+                Some(',') => continue,
+                Some(']') => return Ok(Json::Array(values)),
+                _ => return Err(self.error("expected ',' or ']' in array".to_string())),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, Error> {
+        self.expect('{')?;
+        self.skip_whitespace();
+
+        let mut fields = Vec::new();
+        if self.chars.peek() == Some(&'}') {
+            self.next();
+            return Ok(Json::Object(fields));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(':')?;
+            fields.push((key, self.value()?));
+
+            match self.next() {
+                Some(',') => continue,
+                Some('}') => return Ok(Json::Object(fields)),
+                _ => return Err(self.error("expected ',' or '}' in object".to_string())),
+            }
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use crate::{
+    errors::Error,
+    symbol::Symbol,
+    token::{StringPart, Token, TokenType},
+};
+
+use super::{invalid, FromJson, Json, ToJson};
+
+// {"type": "Identifier", "value": "x", "line": 1, "column": 1}, the value
+// is left out for tokens that do not carry one
+impl ToJson for Token {
+    fn to_json(&self) -> Json {
+        let value = match &self.token_type {
+            TokenType::Identifier(value) => Some(Json::from(value.as_str())),
+            TokenType::String(value)
+            | TokenType::DocComment(value)
+            | TokenType::Comment(value)
+            | TokenType::Whitespace(value) => Some(Json::from(value.as_str())),
+            TokenType::Interpolation(parts) => Some(parts.to_json()),
+            TokenType::Character(value) => Some(Json::from(value.to_string())),
+            TokenType::Number(value) => Some(Json::from(*value)),
+            TokenType::Boolean(value) => Some(Json::from(*value)),
+            _ => None,
+        };
+
+        let mut fields = vec![("type", Json::from(type_name(&self.token_type)))];
+        if let Some(value) = value {
+            fields.push(("value", value));
+        }
+        fields.push(("line", Json::from(self.line)));
+        fields.push(("column", Json::from(self.column)));
+
+        Json::object(fields)
+    }
+}
+
+impl FromJson for Token {
+    fn from_json(json: &Json) -> Result<Self, Error> {
+        let name = json.field("type")?.as_str()?;
+
+        let token_type = match name {
+            "Identifier" => TokenType::Identifier(Symbol::intern(json.field("value")?.as_str()?)),
+            "String" => TokenType::String(json.field("value")?.as_str()?.to_string()),
+            "Interpolation" => TokenType::Interpolation(FromJson::from_json(json.field("value")?)?),
+            "DocComment" => TokenType::DocComment(json.field("value")?.as_str()?.to_string()),
+            "Comment" => TokenType::Comment(json.field("value")?.as_str()?.to_string()),
+            "Whitespace" => TokenType::Whitespace(json.field("value")?.as_str()?.to_string()),
+            "Character" => {
+                let value = json.field("value")?.as_str()?;
+                let mut chars = value.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => TokenType::Character(c),
+                    _ => return Err(invalid(format!("invalid character '{}'", value))),
+                }
+            }
+            "Number" => TokenType::Number(json.field("value")?.as_f64()?),
+            "Boolean" => TokenType::Boolean(json.field("value")?.as_bool()?),
+            _ => KEYWORDS
+                .iter()
+                .find(|token_type| type_name(token_type) == name)
+                .cloned()
+                .ok_or_else(|| invalid(format!("unknown token type '{}'", name)))?,
+        };
+
+        Ok(Token::new(
+            token_type,
+            json.field("line")?.as_usize()?,
+            json.field("column")?.as_usize()?,
+        ))
+    }
+}
+
+// Text is a string and embedded code the array of its tokens
+impl ToJson for StringPart {
+    fn to_json(&self) -> Json {
+        match self {
+            StringPart::Text(text) => Json::from(text.as_str()),
+            StringPart::Code(tokens) => tokens.to_json(),
+        }
+    }
+}
+
+impl FromJson for StringPart {
+    fn from_json(json: &Json) -> Result<Self, Error> {
+        match json {
+            Json::String(text) => Ok(StringPart::Text(text.clone())),
+            _ => Ok(StringPart::Code(FromJson::from_json(json)?)),
+        }
+    }
+}
+
+// Every token type without a value
+const KEYWORDS: &[TokenType] = &[
+    TokenType::Null,
+    TokenType::If,
+    TokenType::Then,
+    TokenType::Else,
+    TokenType::End,
+    TokenType::Function,
+    TokenType::Return,
+    TokenType::Is,
+    TokenType::Include,
+    TokenType::Throw,
+    TokenType::Try,
+    TokenType::Catch,
+    TokenType::Finally,
+    TokenType::Match,
+    TokenType::With,
+    TokenType::When,
+    TokenType::Plus,
+    TokenType::Minus,
+    TokenType::Star,
+    TokenType::Slash,
+    TokenType::Percent,
+    TokenType::Caret,
+    TokenType::Ampersand,
+    TokenType::Pipe,
+    TokenType::Tilde,
+    TokenType::LeftShift,
+    TokenType::RightShift,
+    TokenType::EqualEqual,
+    TokenType::BangEqual,
+    TokenType::Less,
+    TokenType::LessEqual,
+    TokenType::Greater,
+    TokenType::GreaterEqual,
+    TokenType::And,
+    TokenType::Or,
+    TokenType::Bang,
+    TokenType::QuestionQuestion,
+    TokenType::QuestionDot,
+    TokenType::Equal,
+    TokenType::Backslash,
+    TokenType::Comma,
+    TokenType::Semicolon,
+    TokenType::Colon,
+    TokenType::Dot,
+    TokenType::DotDot,
+    TokenType::FatArrow,
+    TokenType::LeftParen,
+    TokenType::RightParen,
+    TokenType::LeftBrace,
+    TokenType::RightBrace,
+    TokenType::LeftBracket,
+    TokenType::RightBracket,
+    TokenType::EOL,
+    TokenType::EOF,
+];
+
+/// The variant name of the token type, e.g. "LeftParen"
+fn type_name(token_type: &TokenType) -> String {
+    let name = format!("{:?}", token_type);
+
+    match name.split_once('(') {
+        Some((name, _)) => name.to_string(),
+        None => name,
+    }
+}
@@ -2,12 +2,15 @@ use std::collections::VecDeque;
 
 use crate::{
     errors::*,
-    token::{Token, TokenType},
+    symbol::Symbol,
+    token::{StringPart, Token, TokenType},
+    unicode,
 };
 
 static KEYWORDS: &[(&str, TokenType)] = &[
     ("true", TokenType::Boolean(true)),
     ("false", TokenType::Boolean(false)),
+    ("null", TokenType::Null),
     ("if", TokenType::If),
     ("else", TokenType::Else),
     ("function", TokenType::Function),
@@ -20,49 +23,612 @@ static KEYWORDS: &[(&str, TokenType)] = &[
     ("character", TokenType::Character(' ')),
     ("then", TokenType::Then),
     ("include", TokenType::Include),
+    ("throw", TokenType::Throw),
+    ("try", TokenType::Try),
+    ("catch", TokenType::Catch),
+    ("finally", TokenType::Finally),
+    ("match", TokenType::Match),
+    ("with", TokenType::With),
+    ("when", TokenType::When),
+    ("Infinity", TokenType::Number(f64::INFINITY)),
+    ("NaN", TokenType::Number(f64::NAN)),
 ];
 
+#[derive(Debug, Clone, Copy)]
+pub struct LexerOptions {
+    /// Keep comments and whitespace in the output as `Comment` and `Whitespace`
+    /// tokens, so tools like the formatter can see them. The parser does not
+    /// accept these tokens.
+    pub lossless: bool,
+    /// Treat `#` as the start of a line comment, on by default. A `#!` shebang on
+    /// the first line is skipped either way.
+    pub hash_comments: bool,
+}
+
+impl Default for LexerOptions {
+    fn default() -> Self {
+        Self {
+            lossless: false,
+            hash_comments: true,
+        }
+    }
+}
+
 pub fn tokenize(input: &str) -> Result<VecDeque<Token>, Error> {
-    let mut tokens = VecDeque::new();
-    let mut line = 1;
-    let mut column = 0;
-    let mut chars = input.chars().peekable();
+    tokenize_with(input, &LexerOptions::default())
+}
+
+/// Tokenizes the input while keeping the comment and whitespace trivia
+pub fn tokenize_lossless(input: &str) -> Result<VecDeque<Token>, Error> {
+    tokenize_with(
+        input,
+        &LexerOptions {
+            lossless: true,
+            ..LexerOptions::default()
+        },
+    )
+}
+
+pub fn tokenize_with(input: &str, options: &LexerOptions) -> Result<VecDeque<Token>, Error> {
+    Lexer::with_options(input, *options).collect()
+}
+
+/// Identifiers follow the Unicode identifier rules, and may also start with an underscore
+fn is_identifier_start(c: char) -> bool {
+    c == '_' || unicode::is_xid_start(c)
+}
+
+/// After the first character identifiers can also hold digits and combining marks
+fn is_identifier_continue(c: char) -> bool {
+    unicode::is_xid_continue(c)
+}
+
+/// Binary operators, commas and the arrow of a `match` arm leave an expression
+/// open at the end of a line
+fn continues_line(token_type: &TokenType) -> bool {
+    matches!(
+        token_type,
+        TokenType::Plus
+            | TokenType::Minus
+            | TokenType::Star
+            | TokenType::Slash
+            | TokenType::Percent
+            | TokenType::Caret
+            | TokenType::Ampersand
+            | TokenType::Pipe
+            | TokenType::LeftShift
+            | TokenType::RightShift
+            | TokenType::EqualEqual
+            | TokenType::BangEqual
+            | TokenType::Less
+            | TokenType::LessEqual
+            | TokenType::Greater
+            | TokenType::GreaterEqual
+            | TokenType::And
+            | TokenType::Or
+            | TokenType::QuestionQuestion
+            | TokenType::Is
+            | TokenType::Equal
+            | TokenType::DotDot
+            | TokenType::FatArrow
+            | TokenType::Comma
+    )
+}
+
+/// Splits the number literal at the start of the text from what follows it, with
+/// its value when it is well formed. Letters, digits and dots right after a number are
+/// taken as part of it, so `0xZZ`, `12ab` or `1.2.3` are reported whole.
+pub(crate) fn number_literal(text: &str) -> (&str, Option<f64>) {
+    let radix = match text.get(..2) {
+        Some("0x") => 16,
+        Some("0o") => 8,
+        Some("0b") => 2,
+        _ => 10,
+    };
+
+    let bytes = text.as_bytes();
+    let mut end = 0;
+
+    while let Some(byte) = bytes.get(end) {
+        match byte {
+            b'e' | b'E' if radix == 10 => {}
+            b'0'..=b'9' | b'a'..=b'z' | b'A'..=b'Z' | b'_' => {}
+            // Two dots start a range, `1..5`. Any other dot belongs to the literal, so
+            // `1.2.3` is one malformed literal rather than `1.2` followed by `.3`.
+            b'.' if bytes.get(end + 1) != Some(&b'.') => {}
+            b'+' | b'-' if radix == 10 && matches!(bytes[end - 1], b'e' | b'E') => {}
+            _ => break,
+        }
+        end += 1;
+    }
+
+    let literal = &text[..end];
+    (literal, number_value(literal, radix))
+}
+
+fn number_value(literal: &str, radix: u32) -> Option<f64> {
+    let digits = match radix {
+        10 => literal,
+        _ => &literal[2..],
+    };
+
+    // Separators only go between two digits
+    let separated = digits.match_indices('_').all(|(i, _)| {
+        let before = digits[..i].chars().next_back();
+        let after = digits[i + 1..].chars().next();
+        matches!((before, after), (Some(a), Some(b)) if a.is_digit(radix) && b.is_digit(radix))
+    });
+    if !separated {
+        return None;
+    }
+
+    let digits = digits.replace('_', "");
+
+    if radix == 10 {
+        // Literals start with a digit or a dot, so words like `inf` never get here
+        return digits.parse().ok();
+    }
+
+    if digits.is_empty() {
+        return None;
+    }
+
+    digits.chars().try_fold(0.0, |value, c| {
+        Some(value * radix as f64 + c.to_digit(radix)? as f64)
+    })
+}
+
+/// Splits a raw string literal at the start of the text from what follows it, with
+/// its value. `None` when the literal is not closed.
+pub(crate) fn raw_string_literal(text: &str) -> Option<(&str, &str)> {
+    let rest = text.strip_prefix('r')?;
+    let hashes = rest.len() - rest.trim_start_matches('#').len();
+    if !rest[hashes..].starts_with('"') {
+        return None;
+    }
+
+    // The closing quote is followed by as many `#` as the opening one is preceded by
+    let start = 1 + hashes + 1;
+    let closing = format!("\"{}", "#".repeat(hashes));
+    let length = text[start..].find(&closing)?;
+
+    Some((
+        &text[..start + length + closing.len()],
+        &text[start..start + length],
+    ))
+}
+
+/// Produces the tokens of a source one at a time, scanning only as far as the
+/// tokens asked for. Errors end the stream after the tokens before them.
+pub struct Lexer<'a> {
+    source: &'a str,
+    /// Byte offset of the next character to scan
+    offset: usize,
+    line: usize,
+    column: usize,
+    group: usize,
+    /// Brackets opened and not yet closed, newlines inside them are whitespace
+    brackets: usize,
+    /// The last token, an operator or comma, carries the expression to the next line
+    continues: bool,
+    options: LexerOptions,
+    /// Tokens scanned but not yet returned
+    tokens: VecDeque<Token>,
+    error: Option<Error>,
+    done: bool,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(source: &'a str) -> Self {
+        Self::with_options(source, LexerOptions::default())
+    }
+
+    pub fn with_options(source: &'a str, options: LexerOptions) -> Self {
+        Self::resume(source, 0, options)
+    }
+
+    /// Starts lexing at a byte offset of the source, which must be on a character
+    /// boundary outside of any string or comment. Lines and columns still count
+    /// from the start of the source.
+    pub fn resume(source: &'a str, offset: usize, options: LexerOptions) -> Self {
+        let before = &source[..offset];
+        let line = 1 + before.matches('\n').count();
+        let column = match before.rfind('\n') {
+            Some(index) => before[index + 1..].chars().count(),
+            None => before.chars().count(),
+        };
+
+        Self::at(source, offset, line, column, options)
+    }
+
+    fn at(
+        source: &'a str,
+        offset: usize,
+        line: usize,
+        column: usize,
+        options: LexerOptions,
+    ) -> Self {
+        Self {
+            source,
+            offset,
+            line,
+            column,
+            group: 0,
+            brackets: 0,
+            continues: false,
+            options,
+            tokens: VecDeque::new(),
+            error: None,
+            done: false,
+        }
+    }
+
+    /// Byte offset up to which the source has been scanned
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The next token, without consuming it
+    pub fn peek(&mut self) -> Option<&Token> {
+        self.peek_nth(0)
+    }
+
+    /// The token `n` places ahead, without consuming anything. `None` past the
+    /// end of the source or at an error.
+    pub fn peek_nth(&mut self, n: usize) -> Option<&Token> {
+        while !self.is_ready(n) {
+            self.advance();
+        }
+
+        self.tokens.get(n)
+    }
+
+    /// Whether the token at `index` of the buffer is final, or the buffer cannot grow to it
+    fn is_ready(&self, index: usize) -> bool {
+        if self.done {
+            return true;
+        }
+
+        // The last token can still change: a backslash is dropped by the newline
+        // after it and whitespace is merged with the whitespace that follows
+        match self.tokens.get(index + 1) {
+            Some(_) => true,
+            None => match self.tokens.get(index) {
+                Some(Token {
+                    token_type: TokenType::Backslash,
+                    ..
+                }) => false,
+                Some(Token {
+                    token_type: TokenType::Whitespace(_),
+                    ..
+                }) => !self.options.lossless,
+                Some(_) => true,
+                None => false,
+            },
+        }
+    }
+
+    fn advance(&mut self) {
+        let count = self.tokens.len();
+        let result = match self.next_char() {
+            Some(c) => {
+                self.column += 1;
+                let result = self.scan(c);
+                self.track(count);
+                result
+            }
+            None => {
+                self.done = true;
+                self.finish()
+            }
+        };
+
+        if let Err(error) = result {
+            self.error = Some(error);
+            self.done = true;
+        }
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.peek_char()?;
+        self.offset += c.len_utf8();
+        Some(c)
+    }
+
+    fn peek_char(&self) -> Option<char> {
+        self.source[self.offset..].chars().next()
+    }
+
+    fn finish(&mut self) -> Result<(), Error> {
+        if self.group > 0 {
+            return Err(UnexpectedEOF::new(self.line, self.column).into());
+        }
+
+        self.tokens.push_back(Token {
+            token_type: TokenType::EOF,
+            line: self.line + 1,
+            column: 1,
+        });
+
+        Ok(())
+    }
+
+    /// Whether the `/` just read starts a `///` comment with nothing but whitespace
+    /// before it on the line. `////` and longer are ordinary comments, and so are
+    /// doc comments inside brackets or on a line that continues an expression.
+    fn is_doc_comment(&self) -> bool {
+        if self.brackets > 0 || self.continues {
+            return false;
+        }
+
+        let start = self.offset - 1;
+        let line = &self.source[..start];
+        let line = &line[line.rfind('\n').map_or(0, |index| index + 1)..];
+
+        let rest = &self.source[self.offset..];
+        rest.starts_with("//") && !rest.starts_with("///") && line.trim().is_empty()
+    }
+
+    /// Whether the `#` just read starts a `#!` line at the very start of the source
+    fn is_shebang(&self) -> bool {
+        self.offset == 1 && self.peek_char() == Some('!')
+    }
+
+    /// Reads a character of a literal's text, keeping track of the lines it spans
+    fn text_char(&mut self) {
+        self.column += 1;
+        if let Some('\n') = self.next_char() {
+            self.line += 1;
+            self.column = 0;
+        }
+    }
 
-    let mut group: usize = 0;
+    /// Reads the escape sequence starting at the next character, a backslash, and
+    /// returns the character it stands for. Errors point at the backslash.
+    fn escape(&mut self) -> Result<char, Error> {
+        let column = self.column + 1;
+        self.next_char(); // Skip the backslash
+        self.column += 1;
 
-    while let Some(c) = chars.next() {
-        column += 1;
+        let c = match self.next_char() {
+            Some(c) => c,
+            None => return Err(InvalidEscapeCharacter::new('\0', self.line, column).into()),
+        };
+        self.column += 1;
+
+        let (digits, complete) = match c {
+            'n' => return Ok('\n'),
+            't' => return Ok('\t'),
+            'r' => return Ok('\r'),
+            '0' => return Ok('\0'),
+            '\'' => return Ok('\''),
+            '"' => return Ok('"'),
+            '\\' => return Ok('\\'),
+            '$' => return Ok('$'),
+            // Exactly two hex digits, `\x41`
+            'x' => {
+                let digits = self.hex_digits(2);
+                let complete = digits.len() == 2;
+                (digits, complete)
+            }
+            // One to six hex digits in braces, `\u{1F600}`
+            'u' if self.peek_char() == Some('{') => {
+                self.next_char();
+                self.column += 1;
+                let digits = self.hex_digits(6);
+
+                let closed = self.peek_char() == Some('}');
+                if closed {
+                    self.next_char();
+                    self.column += 1;
+                }
+                let complete = closed && !digits.is_empty();
+                (digits, complete)
+            }
+            c => return Err(InvalidEscapeCharacter::new(c, self.line, column).into()),
+        };
+
+        let value = match complete {
+            true => u32::from_str_radix(&digits, 16)
+                .ok()
+                .and_then(char::from_u32),
+            false => None,
+        };
+
+        value.ok_or_else(|| {
+            let sequence = match c {
+                'x' => format!("\\x{}", digits),
+                _ if complete => format!("\\u{{{}}}", digits),
+                _ => format!("\\u{{{}", digits),
+            };
+            InvalidEscapeSequence::new(sequence, self.line, column).into()
+        })
+    }
+
+    /// Reads up to `count` hex digits
+    fn hex_digits(&mut self, count: usize) -> String {
+        let mut digits = String::new();
+
+        while let Some(c) = self.peek_char().filter(char::is_ascii_hexdigit) {
+            if digits.len() == count {
+                break;
+            }
+            digits.push(c);
+            self.next_char();
+            self.column += 1;
+        }
+
+        digits
+    }
+
+    /// Scans a raw string like `r"C:\path"` or `r#"say "hi""#`, whose `r` was just read.
+    /// Raw strings keep their text as is, escapes and `${` included.
+    fn raw_string(&mut self) -> Result<(), Error> {
+        let (line, column) = (self.line, self.column);
+        let source = self.source;
+        let start = self.offset - 1;
+
+        let Some((literal, value)) = raw_string_literal(&source[start..]) else {
+            // Like other strings, an unterminated one runs to the end of the file
+            self.skip(&source[self.offset..]);
+            return Err(UnexpectedEOF::new(self.line, self.column).into());
+        };
+
+        self.skip(&literal[1..]);
+        self.tokens.push_back(Token::new(
+            TokenType::String(value.to_string()),
+            line,
+            column,
+        ));
+
+        Ok(())
+    }
+
+    /// Moves past text that was already scanned
+    fn skip(&mut self, text: &str) {
+        self.offset += text.len();
+
+        for c in text.chars() {
+            self.column += 1;
+            if c == '\n' {
+                self.line += 1;
+                self.column = 0;
+            }
+        }
+    }
+
+    /// Scans the tokens of an expression embedded in a string, up to its closing brace
+    fn embedded(&mut self) -> Result<Vec<Token>, Error> {
+        let mut lexer = Lexer::at(
+            self.source,
+            self.offset,
+            self.line,
+            self.column,
+            LexerOptions {
+                lossless: false,
+                ..self.options
+            },
+        );
+        let mut tokens = Vec::new();
+        let mut depth = 0;
+
+        loop {
+            let token = match lexer.next() {
+                Some(token) => token?,
+                None => return Err(UnexpectedEOF::new(lexer.line, lexer.column).into()),
+            };
+
+            match token.token_type {
+                TokenType::LeftBrace => depth += 1,
+                TokenType::RightBrace if depth == 0 => {
+                    tokens.push(Token::new(TokenType::EOF, token.line, token.column));
+                    break;
+                }
+                TokenType::RightBrace => depth -= 1,
+                TokenType::EOL => return Err(UnexpectedEOL::new(token.line, token.column).into()),
+                TokenType::EOF => return Err(UnexpectedEOF::new(lexer.line, lexer.column).into()),
+                _ => {}
+            }
+
+            tokens.push(token);
+        }
+
+        self.offset = lexer.offset;
+        self.line = lexer.line;
+        self.column = lexer.column;
+
+        Ok(tokens)
+    }
+
+    /// Scans the number literal whose first character was just read
+    fn number(&mut self) -> Result<(), Error> {
+        let source = self.source;
+        let start = self.offset - 1;
+        let (literal, value) = number_literal(&source[start..]);
+        let column = self.column;
+
+        // Number literals are ASCII, so bytes and columns line up
+        self.offset = start + literal.len();
+        self.column += literal.len() - 1;
+
+        match value {
+            Some(value) => {
+                self.tokens
+                    .push_back(Token::new(TokenType::Number(value), self.line, column));
+                Ok(())
+            }
+            None => Err(InvalidNumberLiteral::new(literal.to_string(), self.line, column).into()),
+        }
+    }
+
+    /// Keeps count of the brackets and whether the line goes on, for the tokens
+    /// after the first `count` of the buffer
+    fn track(&mut self, count: usize) {
+        for token in self.tokens.iter().skip(count) {
+            match token.token_type {
+                TokenType::LeftParen | TokenType::LeftBracket | TokenType::LeftBrace => {
+                    self.brackets += 1
+                }
+                TokenType::RightParen | TokenType::RightBracket | TokenType::RightBrace => {
+                    self.brackets = self.brackets.saturating_sub(1)
+                }
+                _ => {}
+            }
+
+            match token.token_type {
+                TokenType::Comment(_) | TokenType::DocComment(_) | TokenType::Whitespace(_) => {}
+                ref token_type => self.continues = continues_line(token_type),
+            }
+        }
+    }
+
+    /// Scans the tokens starting with `c`
+    fn scan(&mut self, c: char) -> Result<(), Error> {
         match c {
             // Newline
             '\n' => {
-                line += 1;
-                column = 0;
+                self.line += 1;
+                self.column = 0;
 
                 // if the last added token is a backslash, remove it and continue
                 if let Some(Token {
                     token_type: TokenType::Backslash,
                     ..
-                }) = tokens.back()
+                }) = self.tokens.back()
                 {
-                    tokens.pop_back();
-                    continue;
+                    self.tokens.pop_back();
+                    return Ok(());
+                }
+
+                // Inside brackets and after an operator or comma the expression goes on
+                if self.brackets > 0 || self.continues {
+                    return Ok(());
                 }
 
-                if group > 0 {
-                    return Err(UnexpectedEOL::new(line, column).into());
+                if self.group > 0 {
+                    return Err(UnexpectedEOL::new(self.line, self.column).into());
                 }
 
-                tokens.push_back(Token::new(TokenType::EOL, line, column));
+                self.tokens
+                    .push_back(Token::new(TokenType::EOL, self.line, self.column));
+            }
+
+            // Raw string literal
+            'r' if self.source[self.offset..]
+                .trim_start_matches('#')
+                .starts_with('"') =>
+            {
+                self.raw_string()?
             }
 
             // Identifier
-            'A'..='Z' | 'a'..='z' | '_' => {
-                let cur_col = column;
+            c if is_identifier_start(c) => {
+                let cur_col = self.column;
                 let mut identifier = String::new();
                 identifier.push(c);
-                while let Some(&('A'..='Z' | 'a'..='z' | '0'..='9' | '_')) = chars.peek() {
-                    identifier.push(chars.next().unwrap());
-                    column += 1;
+                while let Some(true) = self.peek_char().map(is_identifier_continue) {
+                    identifier.push(self.next_char().unwrap());
+                    self.column += 1;
                 }
 
                 // Check if it's a keyword
@@ -70,276 +636,402 @@ pub fn tokenize(input: &str) -> Result<VecDeque<Token>, Error> {
                     .iter()
                     .find(|(keyword, _)| *keyword == identifier)
                     .map(|(_, token_type)| token_type.clone())
-                    .unwrap_or(TokenType::Identifier(identifier));
+                    .unwrap_or_else(|| TokenType::Identifier(Symbol::intern(&identifier)));
 
-                tokens.push_back(Token::new(token_type, line, cur_col));
+                self.tokens
+                    .push_back(Token::new(token_type, self.line, cur_col));
             }
 
             // String literal
             '"' => {
-                group += 1;
-                let cur_col = column;
+                self.group += 1;
+                let (cur_line, cur_col) = (self.line, self.column);
                 let mut string = String::new();
-                while let Some(&c) = chars.peek() {
+                let mut parts = Vec::new();
+                while let Some(c) = self.peek_char() {
                     if c == '\\' {
-                        chars.next(); // Skip the backslash
-                        column += 1;
-                        match chars.next() {
-                            Some('n') => string.push('\n'),
-                            Some('t') => string.push('\t'),
-                            Some('r') => string.push('\r'),
-                            Some('0') => string.push('\0'),
-                            Some('\'') => string.push('\''),
-                            Some('\"') => string.push('\"'),
-                            Some('\\') => string.push('\\'),
-                            Some(c) => {
-                                return Err(InvalidEscapeCharacter::new(c, line, column).into())
-                            }
-                            _ => return Err(InvalidEscapeCharacter::new('\0', line, column).into()),
+                        string.push(self.escape()?);
+                        continue;
+                    }
+
+                    // Embedded expression
+                    if c == '$' && self.source[self.offset + 1..].starts_with('{') {
+                        self.next_char();
+                        self.next_char();
+                        self.column += 2;
+
+                        if !string.is_empty() {
+                            parts.push(StringPart::Text(std::mem::take(&mut string)));
                         }
+                        parts.push(StringPart::Code(self.embedded()?));
                         continue;
                     }
 
                     if c == '"' {
-                        chars.next();
-                        column += 1;
-                        group -= 1;
+                        self.next_char();
+                        self.column += 1;
+                        self.group -= 1;
                         break;
                     }
                     string.push(c);
-                    chars.next();
-                    column += 1;
+                    self.text_char();
                 }
 
-                tokens.push_back(Token::new(TokenType::String(string), line, cur_col));
+                let token_type = if parts.is_empty() {
+                    TokenType::String(string)
+                } else {
+                    if !string.is_empty() {
+                        parts.push(StringPart::Text(string));
+                    }
+                    TokenType::Interpolation(parts)
+                };
+
+                self.tokens
+                    .push_back(Token::new(token_type, cur_line, cur_col));
             }
             // Character literal
             '\'' => {
-                group += 1;
-                let cur_col = column;
+                self.group += 1;
+                let (cur_line, cur_col) = (self.line, self.column);
                 let mut string = String::new();
-                while let Some(&c) = chars.peek() {
+                while let Some(c) = self.peek_char() {
                     if c == '\\' {
-                        chars.next(); // Skip the backslash
-                        column += 1;
-                        match chars.next() {
-                            Some('n') => string.push('\n'),
-                            Some('t') => string.push('\t'),
-                            Some('r') => string.push('\r'),
-                            Some('0') => string.push('\0'),
-                            Some('\'') => string.push('\''),
-                            Some('\"') => string.push('\"'),
-                            Some('\\') => string.push('\\'),
-                            Some(c) => {
-                                return Err(InvalidEscapeCharacter::new(c, line, column).into())
-                            }
-                            _ => return Err(InvalidEscapeCharacter::new('\0', line, column).into()),
-                        }
+                        string.push(self.escape()?);
                         continue;
                     }
 
                     if c == '\'' {
-                        chars.next();
-                        column += 1;
-                        group -= 1;
+                        self.next_char();
+                        self.column += 1;
+                        self.group -= 1;
                         break;
                     }
                     string.push(c);
-                    chars.next();
-                    column += 1;
+                    self.text_char();
                 }
 
                 // ensure string length is 1
-                if string.len() != 1 {
-                    return Err(InvalidCharacterLiteral::new(line, cur_col).into());
+                if string.chars().count() != 1 {
+                    return Err(InvalidCharacterLiteral::new(cur_line, cur_col).into());
                 }
 
                 // grab the character from the string
                 let character = string.chars().next().unwrap();
 
-                tokens.push_back(Token::new(TokenType::Character(character), line, cur_col));
-            }
-            // Numbers
-            '0'..='9' => {
-                let cur_col = column;
-                // Primary Number
-                let mut number = String::new();
-                number.push(c);
-                while let Some(&('0'..='9')) = chars.peek() {
-                    number.push(chars.next().unwrap());
-                    column += 1;
-                }
-
-                // Floating points
-                if let Some(&'.') = chars.peek() {
-                    number.push(chars.next().unwrap());
-                    column += 1;
-                    while let Some(&('0'..='9')) = chars.peek() {
-                        number.push(chars.next().unwrap());
-                        column += 1;
-                    }
-                }
-
-                tokens.push_back(Token::new(
-                    TokenType::Number(number.parse().unwrap()),
-                    line,
+                self.tokens.push_back(Token::new(
+                    TokenType::Character(character),
+                    cur_line,
                     cur_col,
                 ));
             }
+            // Numbers, a leading dot like `.5` included
+            '0'..='9' => self.number()?,
+            '.' if matches!(self.peek_char(), Some('0'..='9')) => self.number()?,
 
             // Booleans
             // Booleans are keywords, dummy
 
             // Arithmetic operators
-            '+' => tokens.push_back(Token::new(TokenType::Plus, line, column)),
-            '-' => tokens.push_back(Token::new(TokenType::Minus, line, column)),
-            '*' => tokens.push_back(Token::new(TokenType::Star, line, column)),
+            '+' => self
+                .tokens
+                .push_back(Token::new(TokenType::Plus, self.line, self.column)),
+            '-' => self
+                .tokens
+                .push_back(Token::new(TokenType::Minus, self.line, self.column)),
+            '*' => self
+                .tokens
+                .push_back(Token::new(TokenType::Star, self.line, self.column)),
             '/' => {
                 // Comments
-                if let Some(&'/') = chars.peek() {
-                    chars.next();
-                    column += 1;
-                    while let Some(&c) = chars.peek() {
+                let (cur_line, cur_col) = (self.line, self.column);
+                let mut comment = String::from(c);
+                if self.is_doc_comment() {
+                    let rest = &self.source[self.offset - 1..];
+                    let text = &rest[..rest.find('\n').unwrap_or(rest.len())];
+                    self.offset += text.len() - 1;
+                    self.column += text.chars().count() - 1;
+
+                    self.tokens.push_back(Token::new(
+                        TokenType::DocComment(text.to_string()),
+                        cur_line,
+                        cur_col,
+                    ));
+                    return Ok(());
+                } else if let Some('/') = self.peek_char() {
+                    comment.push(self.next_char().unwrap());
+                    self.column += 1;
+                    while let Some(c) = self.peek_char() {
                         if c == '\n' {
                             break;
                         }
-                        chars.next();
-                        column += 1;
+                        comment.push(c);
+                        self.next_char();
+                        self.column += 1;
                     }
-                    continue;
-                } else if let Some(&'*') = chars.peek() {
-                    chars.next();
-                    column += 1;
-                    while let Some(&c) = chars.peek() {
-                        if c == '*' {
-                            chars.next();
-                            column += 1;
-                            if let Some(&'/') = chars.peek() {
-                                chars.next();
-                                column += 1;
+                } else if let Some('*') = self.peek_char() {
+                    comment.push(self.next_char().unwrap());
+                    self.column += 1;
+                    while let Some(c) = self.next_char() {
+                        comment.push(c);
+                        self.column += 1;
+                        if c == '\n' {
+                            self.line += 1;
+                            self.column = 0;
+                        } else if c == '*' {
+                            if let Some('/') = self.peek_char() {
+                                comment.push(self.next_char().unwrap());
+                                self.column += 1;
                                 break;
                             }
-                        } else {
-                            chars.next();
-                            column += 1;
                         }
                     }
-                    continue;
+                } else {
+                    self.tokens
+                        .push_back(Token::new(TokenType::Slash, self.line, self.column));
+                    return Ok(());
+                }
+
+                if self.options.lossless {
+                    self.tokens.push_back(Token::new(
+                        TokenType::Comment(comment),
+                        cur_line,
+                        cur_col,
+                    ));
+                }
+                return Ok(());
+            }
+            // Hash comments and the shebang, `#!/usr/bin/env alang-main`
+            '#' if self.options.hash_comments || self.is_shebang() => {
+                let (cur_line, cur_col) = (self.line, self.column);
+                let rest = &self.source[self.offset - 1..];
+                let comment = &rest[..rest.find('\n').unwrap_or(rest.len())];
+                self.offset += comment.len() - 1;
+                self.column += comment.chars().count() - 1;
+
+                if self.options.lossless {
+                    self.tokens.push_back(Token::new(
+                        TokenType::Comment(comment.to_string()),
+                        cur_line,
+                        cur_col,
+                    ));
                 }
-                tokens.push_back(Token::new(TokenType::Slash, line, column))
             }
-            '%' => tokens.push_back(Token::new(TokenType::Percent, line, column)),
-            '^' => tokens.push_back(Token::new(TokenType::Caret, line, column)),
+            '%' => self
+                .tokens
+                .push_back(Token::new(TokenType::Percent, self.line, self.column)),
+            '^' => self
+                .tokens
+                .push_back(Token::new(TokenType::Caret, self.line, self.column)),
 
             // Bitwise operators
             // Ampersand is parsed in And (logical) operator
             // Pipe is parsed in Or (logical) operator
-            '~' => tokens.push_back(Token::new(TokenType::Tilde, line, column)),
+            '~' => self
+                .tokens
+                .push_back(Token::new(TokenType::Tilde, self.line, self.column)),
             // LeftShift is parsed in Less (comparsion) operator
             // RightShift is parsed in Greater (comparsion) operator
 
             // Comparison
             '=' => {
-                if let Some(&'=') = chars.peek() {
-                    chars.next();
-                    column += 1;
-                    tokens.push_back(Token::new(TokenType::EqualEqual, line, column));
+                if let Some('=') = self.peek_char() {
+                    self.next_char();
+                    self.column += 1;
+                    self.tokens.push_back(Token::new(
+                        TokenType::EqualEqual,
+                        self.line,
+                        self.column,
+                    ));
+                } else if let Some('>') = self.peek_char() {
+                    self.next_char();
+                    self.column += 1;
+                    self.tokens
+                        .push_back(Token::new(TokenType::FatArrow, self.line, self.column));
                 } else {
-                    tokens.push_back(Token::new(TokenType::Equal, line, column));
+                    self.tokens
+                        .push_back(Token::new(TokenType::Equal, self.line, self.column));
                 }
             }
             '!' => {
-                if let Some(&'=') = chars.peek() {
-                    chars.next();
-                    column += 1;
-                    tokens.push_back(Token::new(TokenType::BangEqual, line, column));
+                if let Some('=') = self.peek_char() {
+                    self.next_char();
+                    self.column += 1;
+                    self.tokens
+                        .push_back(Token::new(TokenType::BangEqual, self.line, self.column));
                 } else {
-                    tokens.push_back(Token::new(TokenType::Bang, line, column));
+                    self.tokens
+                        .push_back(Token::new(TokenType::Bang, self.line, self.column));
                 }
             }
             '<' => {
-                if let Some(&'=') = chars.peek() {
-                    chars.next();
-                    column += 1;
-                    tokens.push_back(Token::new(TokenType::LessEqual, line, column));
-                } else if let Some(&'<') = chars.peek() {
-                    chars.next();
-                    column += 1;
-                    tokens.push_back(Token::new(TokenType::LeftShift, line, column));
+                if let Some('=') = self.peek_char() {
+                    self.next_char();
+                    self.column += 1;
+                    self.tokens
+                        .push_back(Token::new(TokenType::LessEqual, self.line, self.column));
+                } else if let Some('<') = self.peek_char() {
+                    self.next_char();
+                    self.column += 1;
+                    self.tokens
+                        .push_back(Token::new(TokenType::LeftShift, self.line, self.column));
                 } else {
-                    tokens.push_back(Token::new(TokenType::Less, line, column));
+                    self.tokens
+                        .push_back(Token::new(TokenType::Less, self.line, self.column));
                 }
             }
             '>' => {
-                if let Some(&'=') = chars.peek() {
-                    chars.next();
-                    column += 1;
-                    tokens.push_back(Token::new(TokenType::GreaterEqual, line, column));
-                } else if let Some(&'>') = chars.peek() {
-                    chars.next();
-                    column += 1;
-                    tokens.push_back(Token::new(TokenType::RightShift, line, column));
+                if let Some('=') = self.peek_char() {
+                    self.next_char();
+                    self.column += 1;
+                    self.tokens.push_back(Token::new(
+                        TokenType::GreaterEqual,
+                        self.line,
+                        self.column,
+                    ));
+                } else if let Some('>') = self.peek_char() {
+                    self.next_char();
+                    self.column += 1;
+                    self.tokens.push_back(Token::new(
+                        TokenType::RightShift,
+                        self.line,
+                        self.column,
+                    ));
                 } else {
-                    tokens.push_back(Token::new(TokenType::Greater, line, column));
+                    self.tokens
+                        .push_back(Token::new(TokenType::Greater, self.line, self.column));
                 }
             }
 
             // Logical
             '&' => {
-                if let Some(&'&') = chars.peek() {
-                    chars.next();
-                    column += 1;
-                    tokens.push_back(Token::new(TokenType::And, line, column));
+                if let Some('&') = self.peek_char() {
+                    self.next_char();
+                    self.column += 1;
+                    self.tokens
+                        .push_back(Token::new(TokenType::And, self.line, self.column));
                 } else {
-                    tokens.push_back(Token::new(TokenType::Ampersand, line, column));
+                    self.tokens
+                        .push_back(Token::new(TokenType::Ampersand, self.line, self.column));
                 }
             }
             '|' => {
-                if let Some(&'|') = chars.peek() {
-                    chars.next();
-                    column += 1;
-                    tokens.push_back(Token::new(TokenType::Or, line, column));
+                if let Some('|') = self.peek_char() {
+                    self.next_char();
+                    self.column += 1;
+                    self.tokens
+                        .push_back(Token::new(TokenType::Or, self.line, self.column));
                 } else {
-                    tokens.push_back(Token::new(TokenType::Pipe, line, column));
+                    self.tokens
+                        .push_back(Token::new(TokenType::Pipe, self.line, self.column));
                 }
             }
 
+            // Null handling, a `?` on its own is not an operator
+            '?' => match self.peek_char() {
+                Some('?') => {
+                    self.next_char();
+                    self.column += 1;
+                    self.tokens.push_back(Token::new(
+                        TokenType::QuestionQuestion,
+                        self.line,
+                        self.column,
+                    ));
+                }
+                Some('.') => {
+                    self.next_char();
+                    self.column += 1;
+                    self.tokens.push_back(Token::new(
+                        TokenType::QuestionDot,
+                        self.line,
+                        self.column,
+                    ));
+                }
+                _ => return Err(UnhandledCharacter::new(c, self.line, self.column).into()),
+            },
+
             // Assingment
             // Equal is parsed in Comparison operator
 
             // Delimiters
-            ',' => tokens.push_back(Token::new(TokenType::Comma, line, column)),
-            ';' => tokens.push_back(Token::new(TokenType::Semicolon, line, column)),
-            ':' => tokens.push_back(Token::new(TokenType::Colon, line, column)),
-            '.' => tokens.push_back(Token::new(TokenType::Dot, line, column)),
-            '(' => tokens.push_back(Token::new(TokenType::LeftParen, line, column)),
-            ')' => tokens.push_back(Token::new(TokenType::RightParen, line, column)),
-            '{' => tokens.push_back(Token::new(TokenType::LeftBrace, line, column)),
-            '}' => tokens.push_back(Token::new(TokenType::RightBrace, line, column)),
-            '[' => tokens.push_back(Token::new(TokenType::LeftBracket, line, column)),
-            ']' => tokens.push_back(Token::new(TokenType::RightBracket, line, column)),
-            '\\' => tokens.push_back(Token::new(TokenType::Backslash, line, column)),
+            ',' => self
+                .tokens
+                .push_back(Token::new(TokenType::Comma, self.line, self.column)),
+            ';' => self
+                .tokens
+                .push_back(Token::new(TokenType::Semicolon, self.line, self.column)),
+            ':' => self
+                .tokens
+                .push_back(Token::new(TokenType::Colon, self.line, self.column)),
+            '.' => {
+                if let Some('.') = self.peek_char() {
+                    self.next_char();
+                    self.column += 1;
+                    self.tokens
+                        .push_back(Token::new(TokenType::DotDot, self.line, self.column));
+                } else {
+                    self.tokens
+                        .push_back(Token::new(TokenType::Dot, self.line, self.column));
+                }
+            }
+            '(' => self
+                .tokens
+                .push_back(Token::new(TokenType::LeftParen, self.line, self.column)),
+            ')' => self
+                .tokens
+                .push_back(Token::new(TokenType::RightParen, self.line, self.column)),
+            '{' => self
+                .tokens
+                .push_back(Token::new(TokenType::LeftBrace, self.line, self.column)),
+            '}' => self
+                .tokens
+                .push_back(Token::new(TokenType::RightBrace, self.line, self.column)),
+            '[' => {
+                self.tokens
+                    .push_back(Token::new(TokenType::LeftBracket, self.line, self.column))
+            }
+            ']' => {
+                self.tokens
+                    .push_back(Token::new(TokenType::RightBracket, self.line, self.column))
+            }
+            '\\' => self
+                .tokens
+                .push_back(Token::new(TokenType::Backslash, self.line, self.column)),
 
             // Whitespace and unhandled characters
             _ => {
                 if c.is_whitespace() {
-                    continue;
+                    if self.options.lossless {
+                        match self.tokens.back_mut() {
+                            Some(Token {
+                                token_type: TokenType::Whitespace(whitespace),
+                                ..
+                            }) => whitespace.push(c),
+                            _ => self.tokens.push_back(Token::new(
+                                TokenType::Whitespace(c.to_string()),
+                                self.line,
+                                self.column,
+                            )),
+                        }
+                    }
+                    return Ok(());
                 }
 
-                return Err(UnhandledCharacter::new(c, line, column).into());
+                return Err(UnhandledCharacter::new(c, self.line, self.column).into());
             }
         }
-    }
 
-    if group > 0 {
-        return Err(UnexpectedEOF::new(line, column).into());
+        Ok(())
     }
+}
 
-    tokens.push_back(Token {
-        token_type: TokenType::EOF,
-        line: line + 1,
-        column: 1,
-    });
+impl Iterator for Lexer<'_> {
+    type Item = Result<Token, Error>;
 
-    Ok(tokens)
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.peek().is_some() {
+            return self.tokens.pop_front().map(Ok);
+        }
+
+        self.error.take().map(Err)
+    }
 }
@@ -1,9 +1,22 @@
 pub mod errors;
 
 pub mod lexer;
+pub mod symbol;
 pub mod token;
+mod unicode;
 
 pub mod interpreter;
 pub mod types;
+pub mod vm;
 
 pub mod parser;
+
+pub mod formatter;
+
+pub mod linter;
+
+pub mod doc;
+
+pub mod optimizer;
+
+pub mod json;
@@ -0,0 +1,130 @@
+use std::collections::HashSet;
+
+use crate::errors::{Error, InvalidLintConfig};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rule {
+    UnusedVariable,
+    UnusedParameter,
+    UnreachableCode,
+    ConstantCondition,
+    SelfAssignment,
+    MismatchedComparison,
+    ArgumentCount,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 7] = [
+        Rule::UnusedVariable,
+        Rule::UnusedParameter,
+        Rule::UnreachableCode,
+        Rule::ConstantCondition,
+        Rule::SelfAssignment,
+        Rule::MismatchedComparison,
+        Rule::ArgumentCount,
+    ];
+
+    /// The name of the rule as used in config files
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rule::UnusedVariable => "unused-variable",
+            Rule::UnusedParameter => "unused-parameter",
+            Rule::UnreachableCode => "unreachable-code",
+            Rule::ConstantCondition => "constant-condition",
+            Rule::SelfAssignment => "self-assignment",
+            Rule::MismatchedComparison => "mismatched-comparison",
+            Rule::ArgumentCount => "argument-count",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Rule> {
+        Rule::ALL.into_iter().find(|rule| rule.name() == name)
+    }
+}
+
+/// Which lint rules are enabled, all of them are by default
+#[derive(Debug, Clone, Default)]
+pub struct LintConfig {
+    disabled: HashSet<Rule>,
+}
+
+impl LintConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads a config file with one `rule = on|off` per line, `#` starts a comment
+    ///
+    /// ```text
+    /// # allow unused variables in this project
+    /// unused-variable = off
+    /// ```
+    pub fn parse(source: &str) -> Result<Self, Error> {
+        let mut config = Self::new();
+
+        for (index, line) in source.lines().enumerate() {
+            let line_number = index + 1;
+            let line = match line.split_once('#') {
+                Some((line, _)) => line,
+                None => line,
+            };
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let (name, value) = match line.split_once('=') {
+                Some((name, value)) => (name.trim(), value.trim()),
+                None => {
+                    return Err(InvalidLintConfig::new(
+                        format!("expected `rule = on|off`, got '{}'", line.trim()),
+                        line_number,
+                        1,
+                    )
+                    .into())
+                }
+            };
+
+            let rule = match Rule::from_name(name) {
+                Some(rule) => rule,
+                None => {
+                    return Err(InvalidLintConfig::new(
+                        format!("unknown rule '{}'", name),
+                        line_number,
+                        1,
+                    )
+                    .into())
+                }
+            };
+
+            let enabled = match value {
+                "on" | "true" => true,
+                "off" | "false" => false,
+                _ => {
+                    return Err(InvalidLintConfig::new(
+                        format!("expected on or off for '{}', got '{}'", name, value),
+                        line_number,
+                        1,
+                    )
+                    .into())
+                }
+            };
+
+            config.set(rule, enabled);
+        }
+
+        Ok(config)
+    }
+
+    pub fn set(&mut self, rule: Rule, enabled: bool) {
+        if enabled {
+            self.disabled.remove(&rule);
+        } else {
+            self.disabled.insert(rule);
+        }
+    }
+
+    pub fn is_enabled(&self, rule: Rule) -> bool {
+        !self.disabled.contains(&rule)
+    }
+}
@@ -0,0 +1,483 @@
+mod config;
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
+
+use crate::{
+    errors::Error,
+    parser::{
+        ast::{
+            self,
+            visitor::{self, Visitor},
+            ExpressionType,
+        },
+        Parser,
+    },
+    symbol::Symbol,
+    types::{
+        boolean::BooleanVal, character::CharacterVal, number::NumberVal, string::StringVal,
+        Arithmatic, BinaryOperation, Logical, Operator, Relational, RuntimeValue,
+    },
+};
+
+pub use config::{LintConfig, Rule};
+
+#[derive(Debug, Clone)]
+pub struct Warning {
+    pub rule: Rule,
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Warning at line {}, column {}: {} [{}]",
+            self.line,
+            self.column,
+            self.message,
+            self.rule.name()
+        )
+    }
+}
+
+/// Runs the enabled lint rules over the source, returning the warnings in source order
+pub fn lint(source: &str, config: &LintConfig) -> Result<Vec<Warning>, Error> {
+    let program = Parser::produce_ast(source)?;
+
+    let mut linter = Linter {
+        config,
+        scopes: Vec::new(),
+        warnings: Vec::new(),
+    };
+
+    linter.push_scope(&program.body, &[]);
+    linter.visit_program(&program);
+    linter.pop_scope();
+
+    let mut warnings = linter.warnings;
+    warnings.sort_by_key(|warning| (warning.line, warning.column));
+    Ok(warnings)
+}
+
+// The program and every function body get their own scope, `if` blocks share the
+// scope they are in, the same way the interpreter handles environments.
+#[derive(Default)]
+struct Scope {
+    // Where each variable was first assigned
+    variables: Vec<(Symbol, usize, usize)>,
+    parameters: Vec<(Symbol, usize, usize)>,
+    // Every name that is assigned or a parameter somewhere in the scope
+    assigned: HashSet<Symbol>,
+    // Arity of the functions declared in the scope
+    functions: HashMap<Symbol, usize>,
+    reads: HashSet<Symbol>,
+    // Included files run in the same scope and may read any variable
+    has_include: bool,
+}
+
+struct Linter<'a> {
+    config: &'a LintConfig,
+    scopes: Vec<Scope>,
+    warnings: Vec<Warning>,
+}
+
+impl<'a> Linter<'a> {
+    fn warn(&mut self, rule: Rule, message: String, line: usize, column: usize) {
+        if self.config.is_enabled(rule) {
+            self.warnings.push(Warning {
+                rule,
+                message,
+                line,
+                column,
+            });
+        }
+    }
+
+    fn push_scope(&mut self, body: &[ast::Expression], parameters: &[ast::Expression]) {
+        let mut scope = Scope::default();
+
+        for parameter in parameters {
+            if let ExpressionType::Identifier(ast::Identifier { name, .. }) =
+                &parameter.expression_type
+            {
+                scope
+                    .parameters
+                    .push((*name, parameter.line, parameter.column));
+                scope.assigned.insert(*name);
+            }
+        }
+
+        for expression in body {
+            declarations(expression, &mut scope);
+        }
+
+        self.scopes.push(scope);
+    }
+
+    fn pop_scope(&mut self) {
+        let scope = self.scopes.pop().unwrap();
+
+        if !scope.has_include {
+            for (name, line, column) in &scope.variables {
+                if !scope.reads.contains(name) && !name.as_str().starts_with('_') {
+                    self.warn(
+                        Rule::UnusedVariable,
+                        format!("Variable '{}' is assigned but never used", name),
+                        *line,
+                        *column,
+                    );
+                }
+            }
+        }
+
+        for (name, line, column) in &scope.parameters {
+            if !scope.reads.contains(name) && !name.as_str().starts_with('_') {
+                self.warn(
+                    Rule::UnusedParameter,
+                    format!("Parameter '{}' is never used", name),
+                    *line,
+                    *column,
+                );
+            }
+        }
+
+        // Names the function reads from its surroundings count as used there
+        if let Some(parent) = self.scopes.last_mut() {
+            parent.reads.extend(
+                scope
+                    .reads
+                    .into_iter()
+                    .filter(|name| !scope.assigned.contains(name)),
+            );
+        }
+    }
+
+    fn read(&mut self, name: Symbol) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.reads.insert(name);
+        }
+    }
+
+    fn declare(&mut self, name: Symbol, at: &ast::Expression) {
+        let scope = self.scopes.last_mut().unwrap();
+
+        let known = scope.variables.iter().any(|(known, ..)| *known == name)
+            || scope.parameters.iter().any(|(known, ..)| *known == name);
+
+        if !known {
+            scope.variables.push((name, at.line, at.column));
+        }
+    }
+
+    /// The arity of the function a call refers to, when it is certainly a function
+    /// declared in this file and not a variable with the same name.
+    fn known_function(&self, name: Symbol) -> Option<usize> {
+        for scope in self.scopes.iter().rev() {
+            if scope.assigned.contains(&name) {
+                return None;
+            }
+
+            if let Some(arity) = scope.functions.get(&name) {
+                return Some(*arity);
+            }
+        }
+
+        None
+    }
+
+    // `==` and `!=` between literals that can never be compared
+    fn comparison(&mut self, binary: &ast::Binary) {
+        if !matches!(
+            binary.operator,
+            Operator::Relational(Relational::Equal) | Operator::Relational(Relational::NotEqual)
+        ) {
+            return;
+        }
+
+        let (left, right) = match (
+            literal_type(ungroup(&binary.left)),
+            literal_type(ungroup(&binary.right)),
+        ) {
+            (Some(left), Some(right)) => (left, right),
+            _ => return,
+        };
+
+        // Characters can be compared with their numeric value
+        if left == right || (left == "character" && right == "number") {
+            return;
+        }
+
+        self.warn(
+            Rule::MismatchedComparison,
+            format!(
+                "Comparing a {} with a {} is always an invalid operation",
+                left, right
+            ),
+            binary.left.line,
+            binary.left.column,
+        );
+    }
+}
+
+impl Visitor for Linter<'_> {
+    fn visit_block(&mut self, body: &[ast::Expression]) {
+        let mut returned = false;
+        let mut reported = false;
+
+        for expression in body {
+            // Only the first unreachable expression of a block is reported
+            if returned && !reported {
+                self.warn(
+                    Rule::UnreachableCode,
+                    "Unreachable expression after return".to_string(),
+                    expression.line,
+                    expression.column,
+                );
+                reported = true;
+            }
+
+            self.visit_expression(expression);
+            returned = returned || always_returns(expression);
+        }
+    }
+
+    fn visit_identifier(&mut self, identifier: &ast::Identifier) {
+        self.read(identifier.name);
+    }
+
+    fn visit_assignment(&mut self, assignment: &ast::Assignment) {
+        match &assignment.left.expression_type {
+            ExpressionType::Identifier(ast::Identifier { name, .. }) => {
+                if let ExpressionType::Identifier(ast::Identifier { name: right, .. }) =
+                    &ungroup(&assignment.right).expression_type
+                {
+                    if right == name {
+                        self.warn(
+                            Rule::SelfAssignment,
+                            format!("'{}' is assigned to itself", name),
+                            assignment.left.line,
+                            assignment.left.column,
+                        );
+                    }
+                }
+
+                self.declare(*name, &assignment.left);
+            }
+            _ => self.visit_expression(&assignment.left),
+        }
+
+        self.visit_expression(&assignment.right);
+    }
+
+    fn visit_binary(&mut self, binary: &ast::Binary) {
+        self.comparison(binary);
+        visitor::walk_binary(self, binary);
+    }
+
+    fn visit_if(&mut self, cond: &ast::If) {
+        if let Some(value) = constant_value(&cond.condition) {
+            let message = match value {
+                RuntimeValue::Boolean(value) => format!("Condition is always {}", value.value()),
+                _ => "Condition is constant".to_string(),
+            };
+
+            self.warn(
+                Rule::ConstantCondition,
+                message,
+                cond.condition.line,
+                cond.condition.column,
+            );
+        }
+
+        visitor::walk_if(self, cond);
+    }
+
+    fn visit_function(&mut self, function: &ast::Function) {
+        self.push_scope(&function.body, &function.parameters);
+        self.visit_block(&function.body);
+        self.pop_scope();
+    }
+
+    fn visit_call(&mut self, call: &ast::Call) {
+        if let ExpressionType::Identifier(ast::Identifier { name, .. }) = &call.name.expression_type
+        {
+            if let Some(arity) = self.known_function(*name) {
+                if arity != call.parameters.len() {
+                    self.warn(
+                        Rule::ArgumentCount,
+                        format!(
+                            "Function '{}' takes {} arguments but is called with {}",
+                            name,
+                            arity,
+                            call.parameters.len()
+                        ),
+                        call.name.line,
+                        call.name.column,
+                    );
+                }
+            }
+        }
+
+        visitor::walk_call(self, call);
+    }
+}
+
+// Collects what the scope declares, without going into nested functions
+fn declarations(expression: &ast::Expression, scope: &mut Scope) {
+    match &expression.expression_type {
+        ExpressionType::Assignment(assignment) => {
+            if let ExpressionType::Identifier(ast::Identifier { name, .. }) =
+                &assignment.left.expression_type
+            {
+                scope.assigned.insert(*name);
+            }
+            declarations(&assignment.left, scope);
+            declarations(&assignment.right, scope);
+        }
+        ExpressionType::Function(func) => {
+            if let ExpressionType::Identifier(ast::Identifier { name, .. }) =
+                &func.name.expression_type
+            {
+                // Redeclared with a different arity, we cannot tell which one is called
+                match scope.functions.get(name) {
+                    Some(arity) if *arity != func.parameters.len() => {
+                        scope.assigned.insert(*name);
+                    }
+                    _ => {
+                        scope.functions.insert(*name, func.parameters.len());
+                    }
+                }
+            }
+        }
+        ExpressionType::Binary(binary) => {
+            declarations(&binary.left, scope);
+            declarations(&binary.right, scope);
+        }
+        ExpressionType::Unary(unary) => declarations(&unary.right, scope),
+        ExpressionType::Interpolation(interpolation) => {
+            for part in &interpolation.parts {
+                if let ast::InterpolationPart::Expression(part) = part {
+                    declarations(part, scope);
+                }
+            }
+        }
+        ExpressionType::Grouping(group) => declarations(&group.expression, scope),
+        ExpressionType::If(cond) => {
+            declarations(&cond.condition, scope);
+            for expression in cond.body.iter().chain(cond.else_body.iter().flatten()) {
+                declarations(expression, scope);
+            }
+        }
+        ExpressionType::Return(ret) => declarations(&ret.value, scope),
+        ExpressionType::Call(call) => {
+            for argument in &call.parameters {
+                declarations(argument, scope);
+            }
+        }
+        ExpressionType::Member(member) => declarations(&member.object, scope),
+        ExpressionType::Throw(throw) => declarations(&throw.value, scope),
+        ExpressionType::Try(try_block) => {
+            if let Some(ast::Catch { name, .. }) = &try_block.catch {
+                if let ExpressionType::Identifier(ast::Identifier { name, .. }) =
+                    &name.expression_type
+                {
+                    scope.assigned.insert(*name);
+                }
+            }
+
+            let catch = try_block.catch.iter().flat_map(|catch| &catch.body);
+            let finally = try_block.finally.iter().flatten();
+            for expression in try_block.body.iter().chain(catch).chain(finally) {
+                declarations(expression, scope);
+            }
+        }
+        ExpressionType::Match(matching) => {
+            declarations(&matching.value, scope);
+            for arm in &matching.arms {
+                if let ast::Pattern::Binding(binding) = &arm.pattern {
+                    if let ExpressionType::Identifier(ast::Identifier { name, .. }) =
+                        &binding.expression_type
+                    {
+                        scope.assigned.insert(*name);
+                    }
+                }
+
+                for expression in arm.guard.iter().chain(Some(&arm.body)) {
+                    declarations(expression, scope);
+                }
+            }
+        }
+        ExpressionType::Include(include) => {
+            scope.has_include = true;
+            declarations(&include.path, scope);
+        }
+        _ => {}
+    }
+}
+
+fn always_returns(expression: &ast::Expression) -> bool {
+    match &expression.expression_type {
+        ExpressionType::Return(_) => true,
+        ExpressionType::If(cond) => match &cond.else_body {
+            Some(else_body) => {
+                cond.body.iter().any(always_returns) && else_body.iter().any(always_returns)
+            }
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+fn ungroup(expression: &ast::Expression) -> &ast::Expression {
+    match &expression.expression_type {
+        ExpressionType::Grouping(group) => ungroup(&group.expression),
+        _ => expression,
+    }
+}
+
+fn literal_type(expression: &ast::Expression) -> Option<&'static str> {
+    match expression.expression_type {
+        ExpressionType::Number(_) => Some("number"),
+        ExpressionType::String(_) => Some("string"),
+        ExpressionType::Character(_) => Some("character"),
+        ExpressionType::Boolean(_) => Some("boolean"),
+        _ => None,
+    }
+}
+
+/// The value of an expression made up of only literals
+fn constant_value(expression: &ast::Expression) -> Option<RuntimeValue> {
+    match &expression.expression_type {
+        ExpressionType::Number(value) => Some(RuntimeValue::Number(NumberVal::from(*value))),
+        ExpressionType::String(value) => Some(RuntimeValue::String(StringVal::from(value.clone()))),
+        ExpressionType::Character(value) => {
+            Some(RuntimeValue::Character(CharacterVal::from(*value)))
+        }
+        ExpressionType::Boolean(value) => Some(RuntimeValue::Boolean(BooleanVal::from(*value))),
+        ExpressionType::Null => Some(RuntimeValue::Null),
+        ExpressionType::Grouping(group) => constant_value(&group.expression),
+        ExpressionType::Unary(unary) => match (constant_value(&unary.right)?, &unary.operator) {
+            (RuntimeValue::Boolean(value), Operator::Logical(Logical::Not)) => {
+                Some(RuntimeValue::Boolean(BooleanVal::from(!value.value())))
+            }
+            (RuntimeValue::Number(value), Operator::Arithmatic(Arithmatic::Minus)) => {
+                Some(RuntimeValue::Number(NumberVal::from(-value.value())))
+            }
+            (value @ RuntimeValue::Number(_), Operator::Arithmatic(Arithmatic::Plus)) => {
+                Some(value)
+            }
+            _ => None,
+        },
+        ExpressionType::Binary(binary) => {
+            let left = constant_value(&binary.left)?;
+            let right = constant_value(&binary.right)?;
+            left.operation(&right, binary.operator.clone()).ok()
+        }
+        _ => None,
+    }
+}
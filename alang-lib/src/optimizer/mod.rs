@@ -0,0 +1,172 @@
+//! Simplifies programs before they run: operations on literals are computed
+//! ahead of time and `if`s with a literal condition lose the branch that can
+//! never run. Optimized programs behave exactly like the original, including
+//! the values, positions and errors they produce.
+
+use crate::{
+    interpreter,
+    parser::ast::{
+        self,
+        visitor::{self, VisitorMut},
+        ExpressionType,
+    },
+    types::{
+        boolean::BooleanVal, character::CharacterVal, number::NumberVal, string::StringVal,
+        RuntimeType, RuntimeValue,
+    },
+};
+
+pub fn optimize(program: &mut ast::Program) {
+    Optimizer.visit_program_mut(program);
+}
+
+struct Optimizer;
+
+impl Optimizer {
+    /// Optimizes the expressions of the block, replacing `if`s with a literal
+    /// condition by the branch that runs. The last expression is kept as is,
+    /// because its value is the value of the block.
+    fn block(&mut self, body: &mut Vec<ast::Expression>) {
+        self.visit_block_mut(body);
+
+        let count = body.len();
+        let mut optimized = Vec::with_capacity(count);
+
+        for (i, expression) in body.drain(..).enumerate() {
+            match expression.expression_type {
+                // `visit_if_mut` already turned literal conditions into `true`
+                ExpressionType::If(cond)
+                    if i + 1 < count
+                        && matches!(
+                            cond.condition.expression_type,
+                            ExpressionType::Boolean(true)
+                        ) =>
+                {
+                    optimized.extend(cond.body)
+                }
+                expression_type => optimized.push(ast::Expression::new(
+                    expression_type,
+                    expression.line,
+                    expression.column,
+                )),
+            }
+        }
+
+        *body = optimized;
+    }
+}
+
+impl VisitorMut for Optimizer {
+    fn visit_program_mut(&mut self, program: &mut ast::Program) {
+        self.block(&mut program.body);
+    }
+
+    fn visit_expression_mut(&mut self, expression: &mut ast::Expression) {
+        visitor::walk_expression_mut(self, expression);
+
+        if let Some(folded) = fold(expression) {
+            expression.expression_type = folded;
+        }
+    }
+
+    fn visit_if_mut(&mut self, cond: &mut ast::If) {
+        self.visit_expression_mut(&mut cond.condition);
+        self.block(&mut cond.body);
+        if let Some(else_body) = &mut cond.else_body {
+            self.block(else_body);
+        }
+
+        // An `if` whose value is used keeps its own position, so only the
+        // branch that never runs is dropped
+        match cond.condition.expression_type {
+            ExpressionType::Boolean(true) => {
+                cond.else_body = None;
+                cond.else_line = None;
+            }
+            ExpressionType::Boolean(false) => {
+                cond.body = cond.else_body.take().unwrap_or_default();
+                cond.condition.expression_type = ExpressionType::Boolean(true);
+                cond.else_line = None;
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_function_mut(&mut self, function: &mut ast::Function) {
+        self.block(&mut function.body);
+    }
+
+    fn visit_try_mut(&mut self, try_block: &mut ast::Try) {
+        self.block(&mut try_block.body);
+        if let Some(catch) = &mut try_block.catch {
+            self.block(&mut catch.body);
+        }
+        if let Some(finally) = &mut try_block.finally {
+            self.block(finally);
+        }
+    }
+}
+
+/// The literal an operation on literals results in, or `None` when it cannot be
+/// computed ahead of time. Operations that fail are left for the program to report.
+fn fold(expression: &ast::Expression) -> Option<ExpressionType> {
+    let (line, column) = (expression.line, expression.column);
+
+    let value = match &expression.expression_type {
+        ExpressionType::Grouping(grouping) => value(&grouping.expression)?,
+        ExpressionType::Unary(unary) => {
+            interpreter::unary_operation(value(&unary.right)?, &unary.operator, line, column)
+                .ok()?
+        }
+        ExpressionType::Binary(binary) => interpreter::binary_operation(
+            value(&binary.left)?,
+            value(&binary.right)?,
+            &binary.operator,
+            line,
+            column,
+        )
+        .ok()?,
+        ExpressionType::Interpolation(interpolation) => {
+            let mut string = String::new();
+            for part in &interpolation.parts {
+                match part {
+                    ast::InterpolationPart::Text(text) => string.push_str(text),
+                    ast::InterpolationPart::Expression(part) => {
+                        string.push_str(&value(part)?.to_string())
+                    }
+                }
+            }
+
+            return Some(ExpressionType::String(string));
+        }
+        _ => return None,
+    };
+
+    match value.value {
+        RuntimeValue::Number(value) => Some(ExpressionType::Number(value.value)),
+        RuntimeValue::String(value) => Some(ExpressionType::String(value.value.to_string())),
+        RuntimeValue::Character(value) => Some(ExpressionType::Character(value.value)),
+        RuntimeValue::Boolean(value) => Some(ExpressionType::Boolean(value.value)),
+        RuntimeValue::Null => Some(ExpressionType::Null),
+        _ => None,
+    }
+}
+
+/// The value of a literal, at its position
+fn value(expression: &ast::Expression) -> Option<RuntimeType> {
+    let value = match &expression.expression_type {
+        ExpressionType::Number(value) => RuntimeValue::Number(NumberVal::from(*value)),
+        ExpressionType::String(value) => RuntimeValue::String(StringVal::from(value.clone())),
+        ExpressionType::Character(value) => RuntimeValue::Character(CharacterVal::from(*value)),
+        ExpressionType::Boolean(value) => RuntimeValue::Boolean(BooleanVal::from(*value)),
+        ExpressionType::Type(name) => RuntimeValue::Type(name.as_str().into()),
+        ExpressionType::Null => RuntimeValue::Null,
+        _ => return None,
+    };
+
+    Some(RuntimeType {
+        value,
+        line: expression.line,
+        column: expression.column,
+    })
+}
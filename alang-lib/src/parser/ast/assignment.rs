@@ -4,4 +4,6 @@ use super::Expression;
 pub struct Assignment {
     pub left: Box<Expression>,
     pub right: Box<Expression>,
+    /// Text of the `///` comments right above the assignment
+    pub doc: Option<String>,
 }
@@ -5,6 +5,10 @@ pub struct If {
     pub condition: Box<Expression>,
     pub body: Vec<Expression>,
     pub else_body: Option<Vec<Expression>>,
+    /// Line of the `else` keyword, if there is one
+    pub else_line: Option<usize>,
+    /// Line of the `end` keyword closing the block
+    pub end_line: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -0,0 +1,26 @@
+use super::Expression;
+
+#[derive(Debug, Clone)]
+pub struct Throw {
+    pub value: Box<Expression>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Try {
+    pub body: Vec<Expression>,
+    pub catch: Option<Catch>,
+    pub finally: Option<Vec<Expression>>,
+    /// Line of the `finally` keyword, if there is one
+    pub finally_line: Option<usize>,
+    /// Line of the `end` keyword closing the block
+    pub end_line: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Catch {
+    /// The variable the error value is stored in
+    pub name: Box<Expression>,
+    pub body: Vec<Expression>,
+    /// Line of the `catch` keyword
+    pub line: usize,
+}
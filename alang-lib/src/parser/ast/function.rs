@@ -5,10 +5,16 @@ pub struct Function {
     pub name: Box<Expression>,
     pub parameters: Vec<Expression>,
     pub body: Vec<Expression>,
+    /// Line of the `end` keyword closing the function
+    pub end_line: usize,
+    /// Text of the `///` comments right above the function
+    pub doc: Option<String>,
 }
 
 #[derive(Debug, Clone)]
 pub struct Call {
     pub name: Box<Expression>,
     pub parameters: Vec<Expression>,
+    /// `f?.(x)`, which is null instead of a call when the function is null
+    pub optional: bool,
 }
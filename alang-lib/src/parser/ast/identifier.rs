@@ -0,0 +1,15 @@
+use crate::symbol::Symbol;
+
+#[derive(Debug, Clone)]
+pub struct Identifier {
+    pub name: Symbol,
+    /// How many scopes up from the current one the variable lives, filled in by the
+    /// resolver. `None` when it is not known before running.
+    pub depth: Option<usize>,
+}
+
+impl Identifier {
+    pub fn new(name: Symbol) -> Self {
+        Self { name, depth: None }
+    }
+}
@@ -0,0 +1,13 @@
+use super::Expression;
+
+/// A string literal with embedded expressions, e.g. `"Hello ${name}"`
+#[derive(Debug, Clone)]
+pub struct Interpolation {
+    pub parts: Vec<InterpolationPart>,
+}
+
+#[derive(Debug, Clone)]
+pub enum InterpolationPart {
+    Text(String),
+    Expression(Expression),
+}
@@ -0,0 +1,35 @@
+use super::Expression;
+
+/// `match value with` and its arms, the first arm that fits gives the value
+#[derive(Debug, Clone)]
+pub struct Match {
+    pub value: Box<Expression>,
+    pub arms: Vec<Arm>,
+    /// Line of the `end` keyword closing the block
+    pub end_line: usize,
+}
+
+/// `pattern when guard => value`
+#[derive(Debug, Clone)]
+pub struct Arm {
+    pub pattern: Pattern,
+    pub guard: Option<Box<Expression>>,
+    pub body: Box<Expression>,
+    /// Position of the pattern
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    /// `_`, fits any value
+    Wildcard,
+    /// A literal the value has to equal, values of another type never do
+    Literal(Box<Expression>),
+    /// `low..high`, both ends included
+    Range(Box<Expression>, Box<Expression>),
+    /// `is number`, with the type literal the way `is` reads it
+    Type(Box<Expression>),
+    /// A name the value is bound to for the guard and body of the arm, fits any value
+    Binding(Box<Expression>),
+}
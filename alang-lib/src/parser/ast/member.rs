@@ -0,0 +1,11 @@
+use crate::symbol::Symbol;
+
+use super::Expression;
+
+/// `value.name`, or `value?.name` which is null when the value is null
+#[derive(Debug, Clone)]
+pub struct Member {
+    pub object: Box<Expression>,
+    pub name: Symbol,
+    pub optional: bool,
+}
@@ -1,18 +1,30 @@
 mod assignment;
 mod binary;
 mod conditional;
+mod exception;
 mod function;
 mod grouping;
+mod identifier;
 mod include;
+mod interpolation;
+mod matching;
+mod member;
 mod program;
 mod unary;
 
+pub mod visitor;
+
 pub use assignment::Assignment;
 pub use binary::Binary;
 pub use conditional::{If, Return};
+pub use exception::{Catch, Throw, Try};
 pub use function::{Call, Function};
 pub use grouping::Grouping;
+pub use identifier::Identifier;
 pub use include::Include;
+pub use interpolation::{Interpolation, InterpolationPart};
+pub use matching::{Arm, Match, Pattern};
+pub use member::Member;
 pub use program::Program;
 pub use unary::Unary;
 
@@ -36,11 +48,13 @@ impl Expression {
 #[derive(Debug, Clone)]
 pub enum ExpressionType {
     // Literals
-    Identifier(String),
+    Identifier(Identifier),
     Number(f64),
     String(String),
+    Interpolation(Interpolation),
     Character(char),
     Boolean(bool),
+    Null,
     Type(String),
 
     Assignment(Assignment),
@@ -49,9 +63,14 @@ pub enum ExpressionType {
     Grouping(Grouping),
 
     If(If),
+    Match(Match),
     Function(Function),
     Return(Return),
     Call(Call),
+    Member(Member),
+
+    Throw(Throw),
+    Try(Try),
 
     Include(Include),
 }
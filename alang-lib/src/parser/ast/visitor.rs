@@ -0,0 +1,470 @@
+//! Traversal of the syntax tree.
+//!
+//! Implement `Visitor` (or `VisitorMut` to change the tree in place) and override
+//! the methods for the nodes you are interested in. The default methods call the
+//! matching `walk_*` function, which visits the children of the node, so an
+//! override can call it too to keep going deeper.
+//!
+//! ```
+//! use alang_lib::parser::{ast::{visitor::{self, Visitor}, Call}, Parser};
+//!
+//! struct CallCounter(usize);
+//!
+//! impl Visitor for CallCounter {
+//!     fn visit_call(&mut self, call: &Call) {
+//!         self.0 += 1;
+//!         visitor::walk_call(self, call);
+//!     }
+//! }
+//!
+//! let program = Parser::produce_ast("f(g(1), 2)").unwrap();
+//! let mut counter = CallCounter(0);
+//! counter.visit_program(&program);
+//! assert_eq!(counter.0, 2);
+//! ```
+
+use super::{
+    Assignment, Binary, Call, Expression, ExpressionType, Function, Grouping, Identifier, If,
+    Include, Interpolation, InterpolationPart, Match, Member, Pattern, Program, Return, Throw, Try,
+    Unary,
+};
+
+pub trait Visitor {
+    fn visit_program(&mut self, program: &Program) {
+        walk_program(self, program)
+    }
+
+    /// A list of expressions run in order: the program, a function, an `if` branch
+    /// or a part of a `try`
+    fn visit_block(&mut self, body: &[Expression]) {
+        walk_block(self, body)
+    }
+
+    fn visit_expression(&mut self, expression: &Expression) {
+        walk_expression(self, expression)
+    }
+
+    fn visit_identifier(&mut self, _identifier: &Identifier) {}
+
+    fn visit_number(&mut self, _value: f64) {}
+
+    fn visit_string(&mut self, _value: &str) {}
+
+    fn visit_interpolation(&mut self, interpolation: &Interpolation) {
+        walk_interpolation(self, interpolation)
+    }
+
+    fn visit_character(&mut self, _value: char) {}
+
+    fn visit_boolean(&mut self, _value: bool) {}
+
+    fn visit_null(&mut self) {}
+
+    fn visit_type(&mut self, _name: &str) {}
+
+    fn visit_assignment(&mut self, assignment: &Assignment) {
+        walk_assignment(self, assignment)
+    }
+
+    fn visit_binary(&mut self, binary: &Binary) {
+        walk_binary(self, binary)
+    }
+
+    fn visit_unary(&mut self, unary: &Unary) {
+        walk_unary(self, unary)
+    }
+
+    fn visit_grouping(&mut self, grouping: &Grouping) {
+        walk_grouping(self, grouping)
+    }
+
+    fn visit_if(&mut self, cond: &If) {
+        walk_if(self, cond)
+    }
+
+    fn visit_match(&mut self, matching: &Match) {
+        walk_match(self, matching)
+    }
+
+    fn visit_function(&mut self, function: &Function) {
+        walk_function(self, function)
+    }
+
+    fn visit_return(&mut self, ret: &Return) {
+        walk_return(self, ret)
+    }
+
+    fn visit_call(&mut self, call: &Call) {
+        walk_call(self, call)
+    }
+
+    fn visit_member(&mut self, member: &Member) {
+        walk_member(self, member)
+    }
+
+    fn visit_throw(&mut self, throw: &Throw) {
+        walk_throw(self, throw)
+    }
+
+    fn visit_try(&mut self, try_block: &Try) {
+        walk_try(self, try_block)
+    }
+
+    fn visit_include(&mut self, include: &Include) {
+        walk_include(self, include)
+    }
+}
+
+pub fn walk_program<V: Visitor + ?Sized>(visitor: &mut V, program: &Program) {
+    visitor.visit_block(&program.body);
+}
+
+pub fn walk_block<V: Visitor + ?Sized>(visitor: &mut V, body: &[Expression]) {
+    for expression in body {
+        visitor.visit_expression(expression);
+    }
+}
+
+pub fn walk_expression<V: Visitor + ?Sized>(visitor: &mut V, expression: &Expression) {
+    match &expression.expression_type {
+        ExpressionType::Identifier(identifier) => visitor.visit_identifier(identifier),
+        ExpressionType::Number(value) => visitor.visit_number(*value),
+        ExpressionType::String(value) => visitor.visit_string(value),
+        ExpressionType::Interpolation(interpolation) => visitor.visit_interpolation(interpolation),
+        ExpressionType::Character(value) => visitor.visit_character(*value),
+        ExpressionType::Boolean(value) => visitor.visit_boolean(*value),
+        ExpressionType::Null => visitor.visit_null(),
+        ExpressionType::Type(name) => visitor.visit_type(name),
+        ExpressionType::Assignment(assignment) => visitor.visit_assignment(assignment),
+        ExpressionType::Binary(binary) => visitor.visit_binary(binary),
+        ExpressionType::Unary(unary) => visitor.visit_unary(unary),
+        ExpressionType::Grouping(grouping) => visitor.visit_grouping(grouping),
+        ExpressionType::If(cond) => visitor.visit_if(cond),
+        ExpressionType::Match(matching) => visitor.visit_match(matching),
+        ExpressionType::Function(function) => visitor.visit_function(function),
+        ExpressionType::Return(ret) => visitor.visit_return(ret),
+        ExpressionType::Call(call) => visitor.visit_call(call),
+        ExpressionType::Member(member) => visitor.visit_member(member),
+        ExpressionType::Throw(throw) => visitor.visit_throw(throw),
+        ExpressionType::Try(try_block) => visitor.visit_try(try_block),
+        ExpressionType::Include(include) => visitor.visit_include(include),
+    }
+}
+
+pub fn walk_interpolation<V: Visitor + ?Sized>(visitor: &mut V, interpolation: &Interpolation) {
+    for part in &interpolation.parts {
+        if let InterpolationPart::Expression(expression) = part {
+            visitor.visit_expression(expression);
+        }
+    }
+}
+
+pub fn walk_assignment<V: Visitor + ?Sized>(visitor: &mut V, assignment: &Assignment) {
+    visitor.visit_expression(&assignment.left);
+    visitor.visit_expression(&assignment.right);
+}
+
+pub fn walk_binary<V: Visitor + ?Sized>(visitor: &mut V, binary: &Binary) {
+    visitor.visit_expression(&binary.left);
+    visitor.visit_expression(&binary.right);
+}
+
+pub fn walk_unary<V: Visitor + ?Sized>(visitor: &mut V, unary: &Unary) {
+    visitor.visit_expression(&unary.right);
+}
+
+pub fn walk_grouping<V: Visitor + ?Sized>(visitor: &mut V, grouping: &Grouping) {
+    visitor.visit_expression(&grouping.expression);
+}
+
+pub fn walk_if<V: Visitor + ?Sized>(visitor: &mut V, cond: &If) {
+    visitor.visit_expression(&cond.condition);
+    visitor.visit_block(&cond.body);
+    if let Some(else_body) = &cond.else_body {
+        visitor.visit_block(else_body);
+    }
+}
+
+pub fn walk_match<V: Visitor + ?Sized>(visitor: &mut V, matching: &Match) {
+    visitor.visit_expression(&matching.value);
+    for arm in &matching.arms {
+        match &arm.pattern {
+            Pattern::Wildcard => {}
+            Pattern::Literal(expression)
+            | Pattern::Type(expression)
+            | Pattern::Binding(expression) => visitor.visit_expression(expression),
+            Pattern::Range(low, high) => {
+                visitor.visit_expression(low);
+                visitor.visit_expression(high);
+            }
+        }
+        if let Some(guard) = &arm.guard {
+            visitor.visit_expression(guard);
+        }
+        visitor.visit_expression(&arm.body);
+    }
+}
+
+pub fn walk_function<V: Visitor + ?Sized>(visitor: &mut V, function: &Function) {
+    visitor.visit_expression(&function.name);
+    for parameter in &function.parameters {
+        visitor.visit_expression(parameter);
+    }
+    visitor.visit_block(&function.body);
+}
+
+pub fn walk_return<V: Visitor + ?Sized>(visitor: &mut V, ret: &Return) {
+    visitor.visit_expression(&ret.value);
+}
+
+pub fn walk_call<V: Visitor + ?Sized>(visitor: &mut V, call: &Call) {
+    visitor.visit_expression(&call.name);
+    for argument in &call.parameters {
+        visitor.visit_expression(argument);
+    }
+}
+
+pub fn walk_member<V: Visitor + ?Sized>(visitor: &mut V, member: &Member) {
+    visitor.visit_expression(&member.object);
+}
+
+pub fn walk_throw<V: Visitor + ?Sized>(visitor: &mut V, throw: &Throw) {
+    visitor.visit_expression(&throw.value);
+}
+
+pub fn walk_try<V: Visitor + ?Sized>(visitor: &mut V, try_block: &Try) {
+    visitor.visit_block(&try_block.body);
+    if let Some(catch) = &try_block.catch {
+        visitor.visit_expression(&catch.name);
+        visitor.visit_block(&catch.body);
+    }
+    if let Some(finally) = &try_block.finally {
+        visitor.visit_block(finally);
+    }
+}
+
+pub fn walk_include<V: Visitor + ?Sized>(visitor: &mut V, include: &Include) {
+    visitor.visit_expression(&include.path);
+}
+
+/// Like `Visitor`, but with mutable access to the nodes
+pub trait VisitorMut {
+    fn visit_program_mut(&mut self, program: &mut Program) {
+        walk_program_mut(self, program)
+    }
+
+    fn visit_block_mut(&mut self, body: &mut [Expression]) {
+        walk_block_mut(self, body)
+    }
+
+    fn visit_expression_mut(&mut self, expression: &mut Expression) {
+        walk_expression_mut(self, expression)
+    }
+
+    fn visit_identifier_mut(&mut self, _identifier: &mut Identifier) {}
+
+    fn visit_number_mut(&mut self, _value: &mut f64) {}
+
+    fn visit_string_mut(&mut self, _value: &mut String) {}
+
+    fn visit_interpolation_mut(&mut self, interpolation: &mut Interpolation) {
+        walk_interpolation_mut(self, interpolation)
+    }
+
+    fn visit_character_mut(&mut self, _value: &mut char) {}
+
+    fn visit_boolean_mut(&mut self, _value: &mut bool) {}
+
+    fn visit_null_mut(&mut self) {}
+
+    fn visit_type_mut(&mut self, _name: &mut String) {}
+
+    fn visit_assignment_mut(&mut self, assignment: &mut Assignment) {
+        walk_assignment_mut(self, assignment)
+    }
+
+    fn visit_binary_mut(&mut self, binary: &mut Binary) {
+        walk_binary_mut(self, binary)
+    }
+
+    fn visit_unary_mut(&mut self, unary: &mut Unary) {
+        walk_unary_mut(self, unary)
+    }
+
+    fn visit_grouping_mut(&mut self, grouping: &mut Grouping) {
+        walk_grouping_mut(self, grouping)
+    }
+
+    fn visit_if_mut(&mut self, cond: &mut If) {
+        walk_if_mut(self, cond)
+    }
+
+    fn visit_match_mut(&mut self, matching: &mut Match) {
+        walk_match_mut(self, matching)
+    }
+
+    fn visit_function_mut(&mut self, function: &mut Function) {
+        walk_function_mut(self, function)
+    }
+
+    fn visit_return_mut(&mut self, ret: &mut Return) {
+        walk_return_mut(self, ret)
+    }
+
+    fn visit_call_mut(&mut self, call: &mut Call) {
+        walk_call_mut(self, call)
+    }
+
+    fn visit_member_mut(&mut self, member: &mut Member) {
+        walk_member_mut(self, member)
+    }
+
+    fn visit_throw_mut(&mut self, throw: &mut Throw) {
+        walk_throw_mut(self, throw)
+    }
+
+    fn visit_try_mut(&mut self, try_block: &mut Try) {
+        walk_try_mut(self, try_block)
+    }
+
+    fn visit_include_mut(&mut self, include: &mut Include) {
+        walk_include_mut(self, include)
+    }
+}
+
+pub fn walk_program_mut<V: VisitorMut + ?Sized>(visitor: &mut V, program: &mut Program) {
+    visitor.visit_block_mut(&mut program.body);
+}
+
+pub fn walk_block_mut<V: VisitorMut + ?Sized>(visitor: &mut V, body: &mut [Expression]) {
+    for expression in body {
+        visitor.visit_expression_mut(expression);
+    }
+}
+
+pub fn walk_expression_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expression: &mut Expression) {
+    match &mut expression.expression_type {
+        ExpressionType::Identifier(identifier) => visitor.visit_identifier_mut(identifier),
+        ExpressionType::Number(value) => visitor.visit_number_mut(value),
+        ExpressionType::String(value) => visitor.visit_string_mut(value),
+        ExpressionType::Interpolation(interpolation) => {
+            visitor.visit_interpolation_mut(interpolation)
+        }
+        ExpressionType::Character(value) => visitor.visit_character_mut(value),
+        ExpressionType::Boolean(value) => visitor.visit_boolean_mut(value),
+        ExpressionType::Null => visitor.visit_null_mut(),
+        ExpressionType::Type(name) => visitor.visit_type_mut(name),
+        ExpressionType::Assignment(assignment) => visitor.visit_assignment_mut(assignment),
+        ExpressionType::Binary(binary) => visitor.visit_binary_mut(binary),
+        ExpressionType::Unary(unary) => visitor.visit_unary_mut(unary),
+        ExpressionType::Grouping(grouping) => visitor.visit_grouping_mut(grouping),
+        ExpressionType::If(cond) => visitor.visit_if_mut(cond),
+        ExpressionType::Match(matching) => visitor.visit_match_mut(matching),
+        ExpressionType::Function(function) => visitor.visit_function_mut(function),
+        ExpressionType::Return(ret) => visitor.visit_return_mut(ret),
+        ExpressionType::Call(call) => visitor.visit_call_mut(call),
+        ExpressionType::Member(member) => visitor.visit_member_mut(member),
+        ExpressionType::Throw(throw) => visitor.visit_throw_mut(throw),
+        ExpressionType::Try(try_block) => visitor.visit_try_mut(try_block),
+        ExpressionType::Include(include) => visitor.visit_include_mut(include),
+    }
+}
+
+pub fn walk_interpolation_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    interpolation: &mut Interpolation,
+) {
+    for part in &mut interpolation.parts {
+        if let InterpolationPart::Expression(expression) = part {
+            visitor.visit_expression_mut(expression);
+        }
+    }
+}
+
+pub fn walk_assignment_mut<V: VisitorMut + ?Sized>(visitor: &mut V, assignment: &mut Assignment) {
+    visitor.visit_expression_mut(&mut assignment.left);
+    visitor.visit_expression_mut(&mut assignment.right);
+}
+
+pub fn walk_binary_mut<V: VisitorMut + ?Sized>(visitor: &mut V, binary: &mut Binary) {
+    visitor.visit_expression_mut(&mut binary.left);
+    visitor.visit_expression_mut(&mut binary.right);
+}
+
+pub fn walk_unary_mut<V: VisitorMut + ?Sized>(visitor: &mut V, unary: &mut Unary) {
+    visitor.visit_expression_mut(&mut unary.right);
+}
+
+pub fn walk_grouping_mut<V: VisitorMut + ?Sized>(visitor: &mut V, grouping: &mut Grouping) {
+    visitor.visit_expression_mut(&mut grouping.expression);
+}
+
+pub fn walk_if_mut<V: VisitorMut + ?Sized>(visitor: &mut V, cond: &mut If) {
+    visitor.visit_expression_mut(&mut cond.condition);
+    visitor.visit_block_mut(&mut cond.body);
+    if let Some(else_body) = &mut cond.else_body {
+        visitor.visit_block_mut(else_body);
+    }
+}
+
+pub fn walk_match_mut<V: VisitorMut + ?Sized>(visitor: &mut V, matching: &mut Match) {
+    visitor.visit_expression_mut(&mut matching.value);
+    for arm in &mut matching.arms {
+        match &mut arm.pattern {
+            Pattern::Wildcard => {}
+            Pattern::Literal(expression)
+            | Pattern::Type(expression)
+            | Pattern::Binding(expression) => visitor.visit_expression_mut(expression),
+            Pattern::Range(low, high) => {
+                visitor.visit_expression_mut(low);
+                visitor.visit_expression_mut(high);
+            }
+        }
+        if let Some(guard) = &mut arm.guard {
+            visitor.visit_expression_mut(guard);
+        }
+        visitor.visit_expression_mut(&mut arm.body);
+    }
+}
+
+pub fn walk_function_mut<V: VisitorMut + ?Sized>(visitor: &mut V, function: &mut Function) {
+    visitor.visit_expression_mut(&mut function.name);
+    for parameter in &mut function.parameters {
+        visitor.visit_expression_mut(parameter);
+    }
+    visitor.visit_block_mut(&mut function.body);
+}
+
+pub fn walk_return_mut<V: VisitorMut + ?Sized>(visitor: &mut V, ret: &mut Return) {
+    visitor.visit_expression_mut(&mut ret.value);
+}
+
+pub fn walk_call_mut<V: VisitorMut + ?Sized>(visitor: &mut V, call: &mut Call) {
+    visitor.visit_expression_mut(&mut call.name);
+    for argument in &mut call.parameters {
+        visitor.visit_expression_mut(argument);
+    }
+}
+
+pub fn walk_member_mut<V: VisitorMut + ?Sized>(visitor: &mut V, member: &mut Member) {
+    visitor.visit_expression_mut(&mut member.object);
+}
+
+pub fn walk_throw_mut<V: VisitorMut + ?Sized>(visitor: &mut V, throw: &mut Throw) {
+    visitor.visit_expression_mut(&mut throw.value);
+}
+
+pub fn walk_try_mut<V: VisitorMut + ?Sized>(visitor: &mut V, try_block: &mut Try) {
+    visitor.visit_block_mut(&mut try_block.body);
+    if let Some(catch) = &mut try_block.catch {
+        visitor.visit_expression_mut(&mut catch.name);
+        visitor.visit_block_mut(&mut catch.body);
+    }
+    if let Some(finally) = &mut try_block.finally {
+        visitor.visit_block_mut(finally);
+    }
+}
+
+pub fn walk_include_mut<V: VisitorMut + ?Sized>(visitor: &mut V, include: &mut Include) {
+    visitor.visit_expression_mut(&mut include.path);
+}
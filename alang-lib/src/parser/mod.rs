@@ -1,33 +1,50 @@
 // https://en.wikipedia.org/wiki/Order_of_operations#Programming_languages
 
-pub(crate) mod ast;
+pub mod ast;
 
 mod parser_ext;
-use std::collections::VecDeque;
-
 use parser_ext::*;
 
 use crate::{
     errors::*,
-    lexer::tokenize,
-    token::{Token, TokenType},
+    lexer::{Lexer, LexerOptions},
+    symbol::Symbol,
+    token::{StringPart, Token, TokenType},
 };
 
 use self::ast::{ExpressionType, Include};
 
-pub struct Parser {
-    tokens: VecDeque<Token>,
+pub struct Parser<T> {
+    tokens: T,
+    /// The next token, pulled from the tokens as the previous one is eaten
+    current: Option<Token>,
 }
 
-impl Parser {
+impl Parser<Lexer<'_>> {
     pub fn produce_ast(input: &str) -> Result<ast::Program, Error> {
-        let tokens = tokenize(input)?;
-        let mut parser = Parser { tokens };
+        Self::produce_ast_with(input, &LexerOptions::default())
+    }
+
+    /// Like `produce_ast`, lexing the input with the given options. Comments and
+    /// whitespace are never kept, the parser does not accept them.
+    pub fn produce_ast_with(input: &str, options: &LexerOptions) -> Result<ast::Program, Error> {
+        let options = LexerOptions {
+            lossless: false,
+            ..*options
+        };
+        let mut parser = Parser::new(Lexer::with_options(input, options))?;
 
         let body = parser.parse_block(None)?;
 
         Ok(ast::Program { body })
     }
+}
+
+impl<T: Iterator<Item = Result<Token, Error>>> Parser<T> {
+    fn new(mut tokens: T) -> Result<Self, Error> {
+        let current = tokens.next().transpose()?;
+        Ok(Parser { tokens, current })
+    }
 
     fn is_end_token(&self, tokens: Option<&[TokenType]>) -> bool {
         if let Some(tokens) = tokens {
@@ -37,14 +54,51 @@ impl Parser {
         }
     }
 
+    /// Statements end at a newline or a `;`
+    fn is_separator(&self) -> bool {
+        matches!(
+            self.peek().token_type,
+            TokenType::EOL | TokenType::Semicolon
+        )
+    }
+
+    fn skip_separators(&mut self) -> Result<(), Error> {
+        while self.is_separator() {
+            self.eat()?;
+        }
+        Ok(())
+    }
+
     fn parse_block(
         &mut self,
         end_token: Option<&[TokenType]>,
     ) -> Result<Vec<ast::Expression>, Error> {
         let mut body = Vec::new();
+        let mut doc = Vec::new();
 
         while !self.is_eof() && !self.is_end_token(end_token) {
-            let expr = self.parse()?;
+            // Blank lines, lines with only comments and stray semicolons, a blank
+            // line separates doc comments from the declaration they would document
+            if self.is_separator() {
+                self.eat()?;
+                doc.clear();
+                continue;
+            }
+
+            if let TokenType::DocComment(text) = &self.peek().token_type {
+                doc.push(doc_line(text));
+                self.eat()?;
+                if self.peek().token_type == TokenType::EOL {
+                    self.eat()?;
+                }
+                continue;
+            }
+
+            let mut expr = self.parse()?;
+            if !doc.is_empty() {
+                document(&mut expr, doc.join("\n"));
+                doc.clear();
+            }
             body.push(expr);
         }
 
@@ -52,13 +106,10 @@ impl Parser {
     }
 
     fn parse(&mut self) -> Result<ast::Expression, Error> {
-        let expr = self.parse_expression();
+        let expr = self.parse_expression()?;
+        self.skip_separators()?;
 
-        while self.peek().token_type == TokenType::EOL {
-            self.eat()?;
-        }
-
-        expr
+        Ok(expr)
     }
 
     fn parse_expression(&mut self) -> Result<ast::Expression, Error> {
@@ -81,6 +132,7 @@ impl Parser {
                 ExpressionType::Assignment(ast::Assignment {
                     left: Box::new(left),
                     right: Box::new(right),
+                    doc: None,
                 }),
                 line,
                 column,
@@ -93,7 +145,7 @@ impl Parser {
     // Function declaration
     fn parse_function(&mut self) -> Result<ast::Expression, Error> {
         if self.peek().token_type != TokenType::Function {
-            return self.parse_or();
+            return self.parse_coalesce();
         }
 
         self.expect(TokenType::Function)?;
@@ -116,12 +168,25 @@ impl Parser {
 
         self.expect(TokenType::RightParen)?;
 
-        self.expect(TokenType::EOL)?;
+        // The body can start on the same line, `function f() return 1 end`
+        self.skip_separators()?;
 
         let body = self.parse_block(Some(&[TokenType::End]))?;
 
-        self.expect(TokenType::End)?;
-        self.expect(TokenType::EOL)?;
+        let end = self.expect(TokenType::End)?;
+
+        // Nothing else can follow on the line, unless the enclosing block ends there too
+        if !matches!(
+            self.peek().token_type,
+            TokenType::End
+                | TokenType::Else
+                | TokenType::Catch
+                | TokenType::Finally
+                | TokenType::EOF
+        ) && !self.is_separator()
+        {
+            self.expect(TokenType::EOL)?;
+        }
 
         let line = name.line;
         let column = name.column;
@@ -131,6 +196,8 @@ impl Parser {
                 name: Box::new(name),
                 parameters,
                 body,
+                end_line: end.line,
+                doc: None,
             }),
             line,
             column,
@@ -139,6 +206,31 @@ impl Parser {
 
     // Conditional expression (ternary)
 
+    // Null coalescing, the right side is only evaluated when the left is null
+    fn parse_coalesce(&mut self) -> Result<ast::Expression, Error> {
+        let mut left = self.parse_or()?;
+
+        while self.peek().token_type == TokenType::QuestionQuestion {
+            let operator = self.eat()?.into();
+            let right = self.parse_or()?;
+
+            let line = left.line;
+            let column = left.column;
+
+            left = ast::Expression::new(
+                ast::ExpressionType::Binary(ast::Binary {
+                    left: Box::new(left),
+                    operator,
+                    right: Box::new(right),
+                }),
+                line,
+                column,
+            );
+        }
+
+        Ok(left)
+    }
+
     // Logical OR
     fn parse_or(&mut self) -> Result<ast::Expression, Error> {
         let mut left = self.parse_and()?;
@@ -360,10 +452,13 @@ impl Parser {
         }
     }
 
-    // Function call
+    // Function call and member access
     fn parse_function_call(&mut self) -> Result<ast::Expression, Error> {
         let mut left = self.parse_primary()?;
 
+        // Optional call, `f?.(x)`, or optional member access, `e?.message`
+        let mut optional = self.eat_question_dot()?;
+
         if self.peek().token_type == TokenType::LeftParen {
             let parameters = self.parse_arguments()?;
 
@@ -374,15 +469,59 @@ impl Parser {
                 ast::ExpressionType::Call(ast::Call {
                     name: Box::new(left),
                     parameters,
+                    optional,
                 }),
                 line,
                 column,
             );
+
+            optional = self.eat_question_dot()?;
+        }
+
+        while optional || self.peek().token_type == TokenType::Dot {
+            if !optional {
+                self.eat()?; // Eat the dot
+            }
+
+            let token = self.eat()?;
+            let name = match token.token_type {
+                TokenType::Identifier(name) => name,
+                token_type => {
+                    return Err(UnexpectedToken::new(
+                        token_type,
+                        Some(TokenType::Identifier(Symbol::intern(""))),
+                        token.line,
+                        token.column,
+                    )
+                    .into())
+                }
+            };
+
+            left = ast::Expression::new(
+                ast::ExpressionType::Member(ast::Member {
+                    object: Box::new(left),
+                    name,
+                    optional,
+                }),
+                token.line,
+                token.column,
+            );
+
+            optional = self.eat_question_dot()?;
         }
 
         Ok(left)
     }
 
+    fn eat_question_dot(&mut self) -> Result<bool, Error> {
+        if self.peek().token_type != TokenType::QuestionDot {
+            return Ok(false);
+        }
+
+        self.eat()?;
+        Ok(true)
+    }
+
     // Function Arguments
     fn parse_arguments(&mut self) -> Result<Vec<ast::Expression>, Error> {
         let mut arguments = Vec::new();
@@ -417,6 +556,23 @@ impl Parser {
                 token.line,
                 token.column,
             )),
+            TokenType::Interpolation(parts) => {
+                let parts = parts
+                    .into_iter()
+                    .map(|part| match part {
+                        StringPart::Text(text) => Ok(ast::InterpolationPart::Text(text)),
+                        StringPart::Code(tokens) => {
+                            Ok(ast::InterpolationPart::Expression(Self::embedded(tokens)?))
+                        }
+                    })
+                    .collect::<Result<_, Error>>()?;
+
+                Ok(ast::Expression::new(
+                    ExpressionType::Interpolation(ast::Interpolation { parts }),
+                    token.line,
+                    token.column,
+                ))
+            }
             TokenType::Character(value) => Ok(ast::Expression::new(
                 ExpressionType::Character(value),
                 token.line,
@@ -427,8 +583,13 @@ impl Parser {
                 token.line,
                 token.column,
             )),
+            TokenType::Null => Ok(ast::Expression::new(
+                ExpressionType::Null,
+                token.line,
+                token.column,
+            )),
             TokenType::Identifier(value) => Ok(ast::Expression::new(
-                ExpressionType::Identifier(value),
+                ExpressionType::Identifier(ast::Identifier::new(value)),
                 token.line,
                 token.column,
             )),
@@ -469,19 +630,18 @@ impl Parser {
                 let condition = self.parse_expression()?;
 
                 self.expect(TokenType::Then)?;
-
-                while self.peek().token_type == TokenType::EOL {
-                    self.eat()?; // eat any lingering EOLS
-                }
+                self.skip_separators()?;
 
                 let then_branch = self.parse_block(Some(&[TokenType::Else, TokenType::End]))?;
 
-                let else_branch = if self.peek().token_type == TokenType::Else {
-                    Some(self.parse_else()?)
-                } else {
-                    self.expect(TokenType::End)?;
-                    None
-                };
+                let (else_line, else_branch, end_line) =
+                    if self.peek().token_type == TokenType::Else {
+                        let (else_line, else_branch, end_line) = self.parse_else()?;
+                        (Some(else_line), Some(else_branch), end_line)
+                    } else {
+                        let end = self.expect(TokenType::End)?;
+                        (None, None, end.line)
+                    };
 
                 let line = condition.line;
                 let column = condition.column;
@@ -491,19 +651,30 @@ impl Parser {
                         condition: Box::new(condition),
                         body: then_branch,
                         else_body: else_branch,
+                        else_line,
+                        end_line,
                     }),
                     line,
                     column,
                 ))
             }
+            TokenType::Throw => {
+                let value = self.parse_expression()?;
+
+                Ok(ast::Expression::new(
+                    ExpressionType::Throw(ast::Throw {
+                        value: Box::new(value),
+                    }),
+                    token.line,
+                    token.column,
+                ))
+            }
+            TokenType::Try => self.parse_try(token),
+            TokenType::Match => self.parse_match(token),
             TokenType::Return => {
                 let value = self.parse_expression()?;
 
-                if self.tokens.len() > 1 {
-                    while self.peek().token_type == TokenType::EOL {
-                        self.eat()?; // eat any lingering EOLS
-                    }
-                }
+                self.skip_separators()?;
 
                 let line = value.line;
                 let column = value.column;
@@ -522,22 +693,209 @@ impl Parser {
         }
     }
 
-    fn parse_else(&mut self) -> Result<Vec<ast::Expression>, Error> {
-        self.eat()?; // eat the else
+    /// Parses the tokens of an expression embedded in a string, which must hold exactly one
+    fn embedded(tokens: Vec<Token>) -> Result<ast::Expression, Error> {
+        let mut parser = Parser::new(tokens.into_iter().map(Ok))?;
+        let expression = parser.parse_expression()?;
 
-        while self.peek().token_type == TokenType::EOL {
-            self.eat()?; // eat any lingering EOLS
+        if !parser.is_eof() {
+            let token = parser.peek();
+            return Err(UnexpectedToken::new(
+                token.token_type.clone(),
+                Some(TokenType::RightBrace),
+                token.line,
+                token.column,
+            )
+            .into());
         }
 
+        Ok(expression)
+    }
+
+    // Returns the line of the else, the else branch and the line of the closing end
+    fn parse_else(&mut self) -> Result<(usize, Vec<ast::Expression>, usize), Error> {
+        let else_token = self.eat()?; // eat the else
+        self.skip_separators()?;
+
         if self.peek().token_type == TokenType::If {
-            return Ok(vec![self.parse()?]);
+            // else if shares the end of the nested if
+            let nested = self.parse()?;
+            let end_line = match &nested.expression_type {
+                ExpressionType::If(nested) => nested.end_line,
+                _ => nested.line,
+            };
+
+            return Ok((else_token.line, vec![nested], end_line));
         }
 
         let else_branch = self.parse_block(Some(&[TokenType::End, TokenType::Else]))?;
 
-        self.expect(TokenType::End)?;
+        let end = self.expect(TokenType::End)?;
+
+        Ok((else_token.line, else_branch, end.line))
+    }
+
+    // `try ... catch e ... finally ... end`, which needs a catch, a finally or both
+    fn parse_try(&mut self, token: Token) -> Result<ast::Expression, Error> {
+        self.skip_separators()?;
+
+        let body = self.parse_block(Some(&[
+            TokenType::Catch,
+            TokenType::Finally,
+            TokenType::End,
+        ]))?;
+
+        let catch = if self.peek().token_type == TokenType::Catch {
+            let catch = self.eat()?;
+            let name = self.parse_identifier()?;
+            self.skip_separators()?;
+
+            let body = self.parse_block(Some(&[TokenType::Finally, TokenType::End]))?;
+
+            Some(ast::Catch {
+                name: Box::new(name),
+                body,
+                line: catch.line,
+            })
+        } else {
+            None
+        };
+
+        let (finally_line, finally) = if self.peek().token_type == TokenType::Finally {
+            let finally = self.eat()?;
+            self.skip_separators()?;
+
+            let body = self.parse_block(Some(&[TokenType::End]))?;
+            (Some(finally.line), Some(body))
+        } else {
+            (None, None)
+        };
+
+        if catch.is_none() && finally.is_none() {
+            let token = self.peek();
+            return Err(UnexpectedToken::new(
+                token.token_type.clone(),
+                Some(TokenType::Catch),
+                token.line,
+                token.column,
+            )
+            .into());
+        }
+
+        let end = self.expect(TokenType::End)?;
+
+        Ok(ast::Expression::new(
+            ExpressionType::Try(ast::Try {
+                body,
+                catch,
+                finally,
+                finally_line,
+                end_line: end.line,
+            }),
+            token.line,
+            token.column,
+        ))
+    }
+
+    // `match value with`, then the arms up to the `end`, one per line or separated by `;`
+    fn parse_match(&mut self, token: Token) -> Result<ast::Expression, Error> {
+        let value = self.parse_expression()?;
+
+        self.expect(TokenType::With)?;
+
+        let mut arms = Vec::new();
+        loop {
+            // Arms are not declarations, doc comments between them are plain comments
+            while self.is_separator() || matches!(self.peek().token_type, TokenType::DocComment(_))
+            {
+                self.eat()?;
+            }
+            if self.peek().token_type == TokenType::End {
+                break;
+            }
+
+            arms.push(self.parse_arm()?);
+        }
+
+        let end = self.expect(TokenType::End)?;
+
+        Ok(ast::Expression::new(
+            ExpressionType::Match(ast::Match {
+                value: Box::new(value),
+                arms,
+                end_line: end.line,
+            }),
+            token.line,
+            token.column,
+        ))
+    }
+
+    // `pattern when guard => value`
+    fn parse_arm(&mut self) -> Result<ast::Arm, Error> {
+        let (line, column) = (self.peek().line, self.peek().column);
+
+        let pattern = self.parse_pattern()?;
+
+        let guard = if self.peek().token_type == TokenType::When {
+            self.eat()?;
+            Some(Box::new(self.parse_expression()?))
+        } else {
+            None
+        };
+
+        self.expect(TokenType::FatArrow)?;
+        let body = self.parse_expression()?;
+
+        Ok(ast::Arm {
+            pattern,
+            guard,
+            body: Box::new(body),
+            line,
+            column,
+        })
+    }
+
+    fn parse_pattern(&mut self) -> Result<ast::Pattern, Error> {
+        if self.peek().token_type == TokenType::Is {
+            self.eat()?;
+            let type_name = self.parse_primary()?;
+            return Ok(ast::Pattern::Type(Box::new(type_name)));
+        }
+
+        let pattern = self.parse_unary()?;
+
+        match &pattern.expression_type {
+            ExpressionType::Identifier(identifier) if identifier.name.as_str() == "_" => {
+                Ok(ast::Pattern::Wildcard)
+            }
+            ExpressionType::Identifier(_) => Ok(ast::Pattern::Binding(Box::new(pattern))),
+            _ if is_literal(&pattern) => {
+                if self.peek().token_type != TokenType::DotDot {
+                    return Ok(ast::Pattern::Literal(Box::new(pattern)));
+                }
+
+                self.eat()?;
+                let high = self.parse_unary()?;
+                if !is_literal(&high) {
+                    return Err(UnexpectedExpression::new(
+                        high.expression_type,
+                        None,
+                        high.line,
+                        high.column,
+                    )
+                    .into());
+                }
 
-        Ok(else_branch)
+                Ok(ast::Pattern::Range(Box::new(pattern), Box::new(high)))
+            }
+            _ => Err(UnexpectedExpression::new(
+                pattern.expression_type,
+                None,
+                pattern.line,
+                pattern.column,
+            )
+            .into()),
+        }
     }
 
     fn parse_identifier(&mut self) -> Result<ast::Expression, Error> {
@@ -548,7 +906,9 @@ impl Parser {
         } else {
             Err(UnexpectedExpression::new(
                 identifier.expression_type,
-                Some(ExpressionType::Identifier(String::new())),
+                Some(ExpressionType::Identifier(ast::Identifier::new(
+                    Symbol::intern(""),
+                ))),
                 identifier.line,
                 identifier.column,
             )
@@ -556,3 +916,33 @@ impl Parser {
         }
     }
 }
+
+/// The text of a `///` comment line, without the slashes and the space after them
+fn doc_line(comment: &str) -> String {
+    let text = comment.trim_start_matches('/').trim_end();
+    text.strip_prefix(' ').unwrap_or(text).to_string()
+}
+
+/// Literals that can be used as a pattern, a negative number included
+fn is_literal(expression: &ast::Expression) -> bool {
+    match &expression.expression_type {
+        ExpressionType::Number(_)
+        | ExpressionType::String(_)
+        | ExpressionType::Character(_)
+        | ExpressionType::Boolean(_)
+        | ExpressionType::Null => true,
+        ExpressionType::Unary(unary) => {
+            matches!(unary.right.expression_type, ExpressionType::Number(_))
+        }
+        _ => false,
+    }
+}
+
+/// Attaches the doc comments to the declaration, other expressions have no use for them
+fn document(expression: &mut ast::Expression, doc: String) {
+    match &mut expression.expression_type {
+        ExpressionType::Function(function) => function.doc = Some(doc),
+        ExpressionType::Assignment(assignment) => assignment.doc = Some(doc),
+        _ => {}
+    }
+}
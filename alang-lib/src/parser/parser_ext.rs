@@ -15,7 +15,7 @@ pub trait ParserExt {
     fn is_eof(&self) -> bool;
 }
 
-impl ParserExt for Parser {
+impl<T: Iterator<Item = Result<Token, Error>>> ParserExt for Parser<T> {
     fn current_line(&self) -> usize {
         self.peek().line
     }
@@ -25,14 +25,17 @@ impl ParserExt for Parser {
     }
 
     fn peek(&self) -> &Token {
-        &self.tokens[0]
+        self.current
+            .as_ref()
+            .expect("no tokens after the end of the file")
     }
 
     fn eat(&mut self) -> Result<Token, Error> {
-        let token = match self.tokens.pop_front() {
+        let token = match self.current.take() {
             Some(token) => token,
             None => return Err(UnexpectedEOF::new(0, 0).into()),
         };
+        self.current = self.tokens.next().transpose()?;
         Ok(token)
     }
 
@@ -1,11 +1,16 @@
+use crate::symbol::Symbol;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum TokenType {
     // Literals.
-    Identifier(String),
+    Identifier(Symbol),
     String(String),
+    /// A string literal with embedded `${ ... }` expressions
+    Interpolation(Vec<StringPart>),
     Character(char),
     Number(f64),
     Boolean(bool),
+    Null,
 
     // Keywords
     If,
@@ -16,6 +21,13 @@ pub enum TokenType {
     Return,
     Is,
     Include,
+    Throw,
+    Try,
+    Catch,
+    Finally,
+    Match,
+    With,
+    When,
 
     // Arithmatic Operators
     Plus,
@@ -45,6 +57,10 @@ pub enum TokenType {
     Or,
     Bang,
 
+    // Null handling
+    QuestionQuestion,
+    QuestionDot,
+
     // Assignment
     Equal,
 
@@ -54,6 +70,10 @@ pub enum TokenType {
     Semicolon,
     Colon,
     Dot,
+    /// `..` between the ends of a range pattern
+    DotDot,
+    /// `=>` between a pattern and its value
+    FatArrow,
     LeftParen,
     RightParen,
     LeftBrace,
@@ -61,6 +81,13 @@ pub enum TokenType {
     LeftBracket,
     RightBracket,
 
+    /// A `///` comment on its own line, documenting the declaration below it
+    DocComment(String),
+
+    // Trivia, only produced by the lossless lexer
+    Comment(String),
+    Whitespace(String),
+
     // End of Line
     EOL,
 
@@ -84,3 +111,11 @@ impl Token {
         }
     }
 }
+
+/// A piece of an interpolated string literal
+#[derive(Debug, Clone, PartialEq)]
+pub enum StringPart {
+    Text(String),
+    /// The tokens of an embedded expression, ending with an `EOF` at the closing brace
+    Code(Vec<Token>),
+}
@@ -8,6 +8,12 @@ pub struct BooleanVal {
     pub(crate) value: bool,
 }
 
+impl BooleanVal {
+    pub fn value(&self) -> bool {
+        self.value
+    }
+}
+
 impl Debug for BooleanVal {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "{}", self.value.to_string())
@@ -11,6 +11,29 @@ pub struct CharacterVal {
     pub(crate) value: char,
 }
 
+impl CharacterVal {
+    pub fn value(&self) -> char {
+        self.value
+    }
+}
+
+impl CharacterVal {
+    /// The character `by` code points away, which has to be a whole number that
+    /// lands on a valid character
+    fn offset(&self, by: f64) -> Result<CharacterVal, BinaryOperationError> {
+        let code_point = self.value as u32 as f64 + by;
+
+        // `as` saturates, so fractions and numbers outside of u32 do not survive the round trip
+        let value = Some(code_point as u32)
+            .filter(|code| *code as f64 == code_point)
+            .and_then(char::from_u32);
+
+        value
+            .map(|value| CharacterVal { value })
+            .ok_or(BinaryOperationError::CharacterOutOfRange(code_point))
+    }
+}
+
 impl Debug for CharacterVal {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "\"{}\"", self.value)
@@ -40,7 +63,7 @@ impl BinaryOperation for CharacterVal {
                 Operator::Arithmatic(Arithmatic::Plus) => {
                     let mut new_value = String::from(self.value);
                     new_value.push_str(&other.value.to_string());
-                    Ok(RuntimeValue::String(StringVal { value: new_value }))
+                    Ok(RuntimeValue::String(StringVal::from(new_value)))
                 }
                 // Equal
                 Operator::Relational(Relational::Equal) => Ok(RuntimeValue::Boolean(BooleanVal {
@@ -52,6 +75,27 @@ impl BinaryOperation for CharacterVal {
                         value: self.value != other.value,
                     }))
                 }
+                // Characters are ordered by their code points
+                Operator::Relational(Relational::LessThan) => {
+                    Ok(RuntimeValue::Boolean(BooleanVal {
+                        value: self.value < other.value,
+                    }))
+                }
+                Operator::Relational(Relational::LessThanOrEqual) => {
+                    Ok(RuntimeValue::Boolean(BooleanVal {
+                        value: self.value <= other.value,
+                    }))
+                }
+                Operator::Relational(Relational::GreaterThan) => {
+                    Ok(RuntimeValue::Boolean(BooleanVal {
+                        value: self.value > other.value,
+                    }))
+                }
+                Operator::Relational(Relational::GreaterThanOrEqual) => {
+                    Ok(RuntimeValue::Boolean(BooleanVal {
+                        value: self.value >= other.value,
+                    }))
+                }
                 _ => Err(BinaryOperationError::InvalidOperator),
             };
         }
@@ -66,22 +110,10 @@ impl BinaryOperation for CharacterVal {
             let other = other.unwrap();
             return match operator {
                 Operator::Arithmatic(Arithmatic::Plus) => {
-                    // get numeric value of character
-                    let mut new_value = self.value as u8;
-                    new_value += other.value as u8;
-
-                    // back to character
-                    let new_value = new_value as char;
-                    Ok(RuntimeValue::Character(CharacterVal { value: new_value }))
+                    self.offset(other.value).map(RuntimeValue::Character)
                 }
                 Operator::Arithmatic(Arithmatic::Minus) => {
-                    // get numeric value of character
-                    let mut new_value = self.value as u8;
-                    new_value -= other.value as u8;
-
-                    // back to character
-                    let new_value = new_value as char;
-                    Ok(RuntimeValue::Character(CharacterVal { value: new_value }))
+                    self.offset(-other.value).map(RuntimeValue::Character)
                 }
                 // Equal
                 Operator::Relational(Relational::Equal) => Ok(RuntimeValue::Boolean(BooleanVal {
@@ -0,0 +1,56 @@
+use std::{
+    fmt::{Debug, Display},
+    rc::Rc,
+};
+
+use crate::{
+    symbol::Symbol,
+    vm::{Prototype, Snapshot},
+};
+
+/// A function compiled for the virtual machine, together with the variables
+/// it could see when it was created
+#[derive(Clone)]
+pub struct ClosureVal {
+    pub prototype: Rc<Prototype>,
+    pub snapshot: Rc<Snapshot>,
+}
+
+impl ClosureVal {
+    pub fn new(prototype: Rc<Prototype>, snapshot: Rc<Snapshot>) -> Self {
+        Self {
+            prototype,
+            snapshot,
+        }
+    }
+
+    /// Number of parameters the function declares
+    pub fn arity(&self) -> usize {
+        self.prototype.arity
+    }
+
+    /// Names of the parameters, which take the first slots
+    pub fn parameters(&self) -> &[Symbol] {
+        &self.prototype.slots[..self.prototype.arity]
+    }
+
+    /// Text of the doc comments above the declaration
+    pub fn doc(&self) -> Option<&str> {
+        self.prototype.doc.as_deref()
+    }
+}
+
+impl Debug for ClosureVal {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("ClosureVal")
+            .field("name", &self.prototype.name)
+            .field("arity", &self.prototype.arity)
+            .finish()
+    }
+}
+
+impl Display for ClosureVal {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Function: {}", self.prototype.name)
+    }
+}
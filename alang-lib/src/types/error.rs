@@ -0,0 +1,75 @@
+use std::{
+    fmt::{Debug, Display},
+    rc::Rc,
+};
+
+use crate::errors::Error;
+
+use super::{number::NumberVal, string::StringVal, RuntimeValue};
+
+/// What a `catch` gets: a runtime error, or the value of a `throw`
+#[derive(Clone)]
+pub struct ErrorVal {
+    pub(crate) message: Rc<str>,
+    /// Name of the kind of error, e.g. `UndefinedVariable`, or `Thrown` for a `throw`
+    pub(crate) code: Rc<str>,
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+}
+
+impl ErrorVal {
+    pub fn new(message: &str, code: &str, line: usize, column: usize) -> Self {
+        Self {
+            message: message.into(),
+            code: code.into(),
+            line,
+            column,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// The fields scripts can read, `e.message`
+    pub fn field(&self, name: &str) -> Option<RuntimeValue> {
+        match name {
+            "message" => Some(RuntimeValue::String(StringVal {
+                value: self.message.clone(),
+            })),
+            "code" => Some(RuntimeValue::String(StringVal {
+                value: self.code.clone(),
+            })),
+            "line" => Some(RuntimeValue::Number(NumberVal::from(self.line as f64))),
+            "column" => Some(RuntimeValue::Number(NumberVal::from(self.column as f64))),
+            _ => None,
+        }
+    }
+}
+
+impl From<&Error> for ErrorVal {
+    fn from(error: &Error) -> Self {
+        // A rethrown error keeps where it first happened
+        if let Some(thrown) = error.thrown() {
+            return thrown.clone();
+        }
+
+        Self::new(error.message(), error.code(), error.line(), error.column())
+    }
+}
+
+impl Debug for ErrorVal {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}({:?})", self.code, self.message)
+    }
+}
+
+impl Display for ErrorVal {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
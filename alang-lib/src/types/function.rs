@@ -1,27 +1,52 @@
-use std::fmt::Display;
+use std::{fmt::Display, rc::Rc};
 
-use crate::{interpreter::Environment, parser::ast};
+use crate::{interpreter::Environment, parser::ast, symbol::Symbol};
 
+/// A function declared by a script. Copies share the declaration, and the
+/// environment only copies the variables of its innermost scope.
 #[derive(Debug, Clone)]
 pub struct FunctionVal {
-    pub declaration: ast::Function,
+    pub declaration: Rc<ast::Function>,
     pub env: Environment,
 }
 
 impl FunctionVal {
-    pub fn new(declaration: ast::Function, env: Environment) -> Self {
+    pub fn new(declaration: Rc<ast::Function>, env: Environment) -> Self {
         Self { declaration, env }
     }
+
+    pub fn name(&self) -> Symbol {
+        match &self.declaration.name.expression_type {
+            ast::ExpressionType::Identifier(identifier) => identifier.name,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Number of parameters the function declares
+    pub fn arity(&self) -> usize {
+        self.declaration.parameters.len()
+    }
+
+    /// Names of the parameters
+    pub fn parameters(&self) -> Vec<Symbol> {
+        self.declaration
+            .parameters
+            .iter()
+            .filter_map(|parameter| match &parameter.expression_type {
+                ast::ExpressionType::Identifier(identifier) => Some(identifier.name),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Text of the doc comments above the declaration
+    pub fn doc(&self) -> Option<&str> {
+        self.declaration.doc.as_deref()
+    }
 }
 
 impl Display for FunctionVal {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let name = &self.declaration.name.expression_type;
-        let name = match name {
-            ast::ExpressionType::Identifier(identifier) => identifier,
-            _ => unreachable!(),
-        };
-
-        write!(f, "Function: {}", name)
+        write!(f, "Function: {}", self.name())
     }
 }
@@ -1,8 +1,13 @@
+use std::rc::Rc;
+
 use crate::token::{Token, TokenType};
 
 pub mod boolean;
 pub mod character;
+pub mod closure;
+pub mod error;
 pub mod function;
+pub mod native_function;
 pub mod number;
 pub mod string;
 // mod array;
@@ -17,12 +22,15 @@ pub struct RuntimeType {
 
 #[derive(Debug, Clone)]
 pub enum RuntimeValue {
-    Type(String),
+    Type(Rc<str>),
     String(string::StringVal),
     Number(number::NumberVal),
     Boolean(boolean::BooleanVal),
     Character(character::CharacterVal),
     Function(function::FunctionVal),
+    NativeFunction(native_function::NativeFunctionVal),
+    Closure(closure::ClosureVal),
+    Error(error::ErrorVal),
     Return(Box<RuntimeType>),
     // Array(array::ArrayVal),
     // Object(object::ObjectVal),
@@ -38,16 +46,19 @@ impl BinaryOperation for RuntimeValue {
         match operator {
             Operator::Relational(Relational::Is) => {
                 let other = match other {
-                    RuntimeValue::Type(other) => other,
+                    RuntimeValue::Type(other) => &**other,
                     RuntimeValue::Number(_) => "number",
                     RuntimeValue::String(_) => "string",
                     RuntimeValue::Boolean(_) => "boolean",
                     RuntimeValue::Character(_) => "character",
+                    RuntimeValue::Null => "null",
                     _ => return Err(BinaryOperationError::InvalidOperationType),
                 };
 
                 match self {
-                    RuntimeValue::Function(_) => {
+                    RuntimeValue::Function(_)
+                    | RuntimeValue::NativeFunction(_)
+                    | RuntimeValue::Closure(_) => {
                         return Ok(RuntimeValue::Boolean(boolean::BooleanVal {
                             value: other == "function",
                         }))
@@ -72,9 +83,18 @@ impl BinaryOperation for RuntimeValue {
                             value: other == "character",
                         }))
                     }
-                    _ => return Ok(RuntimeValue::Boolean(boolean::BooleanVal { value: false })),
+                    _ => {
+                        return Ok(RuntimeValue::Boolean(boolean::BooleanVal {
+                            value: matches!(self, RuntimeValue::Null) && other == "null",
+                        }))
+                    }
                 };
             }
+            // The interpreter and the vm only get to the right side when the left is null
+            Operator::Logical(Logical::Coalesce) => match self {
+                RuntimeValue::Null => Ok(other.clone()),
+                _ => Ok(self.clone()),
+            },
             _ => {
                 match self {
                     RuntimeValue::String(value) => value.operation(other, operator),
@@ -84,7 +104,8 @@ impl BinaryOperation for RuntimeValue {
                     // RuntimeValue::Array(value) => value.operation(other, operator),
                     // RuntimeValue::Object(value) => value.operation(other, operator),
                     // RuntimeValue::Function(value) => value.operation(other, operator),
-                    // RuntimeValue::Null => Err(BinaryOperationError::Null),
+                    // Reported with both sides, so the message shows what was null
+                    RuntimeValue::Null => Err(BinaryOperationError::InvalidOperationType),
                     _ => Err(BinaryOperationError::InvalidOperation),
                 }
             }
@@ -94,7 +115,13 @@ impl BinaryOperation for RuntimeValue {
 
 impl std::fmt::Display for RuntimeType {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        match &self.value {
+        self.value.fmt(f)
+    }
+}
+
+impl std::fmt::Display for RuntimeValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
             RuntimeValue::Type(value) => write!(f, "Type: {}", value),
             RuntimeValue::String(val) => val.fmt(f),
             RuntimeValue::Number(val) => val.fmt(f),
@@ -102,6 +129,9 @@ impl std::fmt::Display for RuntimeType {
             RuntimeValue::Character(val) => val.fmt(f),
             RuntimeValue::Return(val) => val.fmt(f),
             RuntimeValue::Function(val) => val.fmt(f),
+            RuntimeValue::NativeFunction(val) => val.fmt(f),
+            RuntimeValue::Closure(val) => val.fmt(f),
+            RuntimeValue::Error(val) => val.fmt(f),
 
             // RuntimeValue::Array(val) => val.fmt(f),
             // RuntimeValue::Object(val) => val.fmt(f),
@@ -115,6 +145,8 @@ pub enum BinaryOperationError {
     InvalidOperationType,
     InvalidOperation,
     InvalidOperator,
+    /// Character arithmetic landed on a number that is not a character
+    CharacterOutOfRange(f64),
 }
 
 pub trait BinaryOperation {
@@ -158,9 +190,10 @@ pub enum Relational {
 
 #[derive(Debug, Clone)]
 pub enum Logical {
-    And, // &&
-    Or,  // ||
-    Not, // !
+    And,      // &&
+    Or,       // ||
+    Not,      // !
+    Coalesce, // ??
 }
 
 impl std::fmt::Display for Operator {
@@ -187,6 +220,7 @@ impl std::fmt::Display for Operator {
             Operator::Logical(Logical::And) => write!(f, "&&"),
             Operator::Logical(Logical::Or) => write!(f, "||"),
             Operator::Logical(Logical::Not) => write!(f, "!"),
+            Operator::Logical(Logical::Coalesce) => write!(f, "??"),
         }
     }
 }
@@ -210,6 +244,7 @@ impl From<Token> for Operator {
             TokenType::And => Operator::Logical(Logical::And),
             TokenType::Or => Operator::Logical(Logical::Or),
             TokenType::Bang => Operator::Logical(Logical::Not),
+            TokenType::QuestionQuestion => Operator::Logical(Logical::Coalesce),
 
             _ => panic!("Invalid token type for operator"),
         }
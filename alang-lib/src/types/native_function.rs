@@ -0,0 +1,52 @@
+use std::{
+    fmt::{Debug, Display},
+    rc::Rc,
+};
+
+use crate::{errors::Error, symbol::Symbol};
+
+use super::{RuntimeType, RuntimeValue};
+
+pub type NativeFunction = dyn Fn(Vec<RuntimeType>) -> Result<RuntimeValue, Error>;
+
+/// A function implemented in Rust that scripts can call like any other function
+#[derive(Clone)]
+pub struct NativeFunctionVal {
+    pub name: Symbol,
+    /// Number of arguments the function takes, `None` accepts any amount
+    pub arity: Option<usize>,
+    pub function: Rc<NativeFunction>,
+}
+
+impl NativeFunctionVal {
+    pub fn new(
+        name: &str,
+        arity: Option<usize>,
+        function: impl Fn(Vec<RuntimeType>) -> Result<RuntimeValue, Error> + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            arity,
+            function: Rc::new(function),
+        }
+    }
+
+    pub fn call(&self, args: Vec<RuntimeType>) -> Result<RuntimeValue, Error> {
+        (self.function)(args)
+    }
+}
+
+impl Debug for NativeFunctionVal {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("NativeFunctionVal")
+            .field("name", &self.name)
+            .field("arity", &self.arity)
+            .finish()
+    }
+}
+
+impl Display for NativeFunctionVal {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Function: {}", self.name)
+    }
+}
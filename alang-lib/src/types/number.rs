@@ -11,6 +11,12 @@ pub struct NumberVal {
     pub(crate) value: f64,
 }
 
+impl NumberVal {
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+}
+
 impl Debug for NumberVal {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "{}", self.value.to_string())
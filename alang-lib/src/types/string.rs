@@ -4,11 +4,21 @@ use super::{
 };
 use crate::token::{Token, TokenType};
 
-use std::fmt::{Debug, Display};
+use std::{
+    fmt::{Debug, Display},
+    rc::Rc,
+};
 
+/// Strings are immutable, so copies of a value share the same text
 #[derive(Clone)]
 pub struct StringVal {
-    pub(crate) value: String,
+    pub(crate) value: Rc<str>,
+}
+
+impl StringVal {
+    pub fn value(&self) -> &str {
+        &self.value
+    }
 }
 
 impl Debug for StringVal {
@@ -36,9 +46,10 @@ impl BinaryOperation for StringVal {
 
         match operator {
             Operator::Arithmatic(Arithmatic::Plus) => {
-                let mut new_value = self.value.clone();
+                let mut new_value = String::with_capacity(self.value.len() + other.value.len());
+                new_value.push_str(&self.value);
                 new_value.push_str(&other.value);
-                Ok(RuntimeValue::String(StringVal { value: new_value }))
+                Ok(RuntimeValue::String(StringVal::from(new_value)))
             }
             // Equal
             Operator::Relational(Relational::Equal) => Ok(RuntimeValue::Boolean(BooleanVal {
@@ -55,14 +66,14 @@ impl BinaryOperation for StringVal {
 
 impl From<String> for StringVal {
     fn from(s: String) -> Self {
-        StringVal { value: s }
+        StringVal { value: s.into() }
     }
 }
 
 impl From<Token> for StringVal {
     fn from(token: Token) -> Self {
         match token.token_type {
-            TokenType::String(value) => StringVal { value },
+            TokenType::String(value) => StringVal::from(value),
             _ => unreachable!(),
         }
     }
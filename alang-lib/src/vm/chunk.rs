@@ -0,0 +1,160 @@
+use std::rc::Rc;
+
+use crate::{
+    parser::ast,
+    symbol::Symbol,
+    types::{Operator, RuntimeValue},
+};
+
+#[derive(Debug, Clone)]
+pub enum Instruction {
+    /// Pushes a value from the constants of the chunk
+    Constant(usize),
+    Null,
+    Load(Access),
+    /// Like `Load`, for the function of a call, which also has to be callable
+    LoadFunction(Access),
+    /// Like `LoadFunction`, but a null function is left as the result of the call
+    /// and jumps over its arguments
+    LoadOptionalFunction(Access, usize),
+    /// Stores the value on top of the stack, leaving it there
+    Store(Access),
+    /// Binds the value on top of the stack for a `match` arm, leaving it there
+    /// and keeping what the variable held before for `Unbind`
+    Bind(Access),
+    /// Puts back what the innermost binding of the frame shadowed
+    Unbind,
+    Pop,
+    Binary(Operator),
+    Unary(Operator),
+    /// Joins the text of the values on top of the stack into a string
+    Interpolate(usize),
+    /// Pops the condition of an `if` and jumps when it is false
+    JumpUnless(usize),
+    Jump(usize),
+    /// Jumps over the right side of a `??` when the value on top of the stack is
+    /// not null, leaving it there, otherwise pops it
+    JumpUnlessNull(usize),
+    /// Moves the value on top of the stack to the position of the instruction
+    Reposition,
+    /// Creates a function from one of the prototypes of the chunk
+    Function(usize),
+    /// Calls the function below the arguments, `name` is the name it was called by
+    Call {
+        arguments: usize,
+        name: Symbol,
+    },
+    Return,
+    /// A `return` used as a value instead of a statement, the interpreter
+    /// passes these along until they reach a block
+    WrapReturn,
+    /// Returns when the value on top of the stack is a wrapped return
+    ExitIfReturn,
+    /// Jumps when the value on top of the stack is a wrapped return
+    BreakIfReturn(usize),
+    /// Reads a member of the value on top of the stack, `optional` leaves null as it is
+    Member {
+        name: Symbol,
+        optional: bool,
+    },
+    /// Pops a value and throws it
+    Throw,
+    /// Catches errors until the matching `EndTry` by jumping to the handler,
+    /// with the stack as it was here and the error value on top
+    Try(usize),
+    EndTry,
+    /// Pops the literal of a `match` arm and pushes whether the value below it equals it
+    MatchLiteral,
+    /// Pops both ends of a range pattern and pushes whether the value below them is within
+    MatchRange,
+    /// Pops the type of an `is` pattern and pushes whether the value below it has that type
+    MatchType,
+    /// Pops the value of a `match` that no arm accepted and fails with it
+    Unmatched,
+    /// Runs the file named by the value on top of the stack, the position
+    /// is that of the path expression
+    Include {
+        line: usize,
+        column: usize,
+    },
+    InvalidAssignment,
+    InvalidFunctionName,
+    /// End of the code, a function returns the last value at the position it was called from
+    End,
+}
+
+/// Where a variable lives
+#[derive(Debug, Clone, Copy)]
+pub enum Access {
+    /// A variable of the program, kept by name in the environment
+    Global(Symbol),
+    /// A parameter or variable of the running function
+    Local { slot: usize, name: Symbol },
+    /// A variable of an enclosing function, or of the program when `slot` is `None`,
+    /// read from the copy the function took when it was created. `depth` counts
+    /// the functions between them.
+    Captured {
+        depth: usize,
+        slot: Option<usize>,
+        name: Symbol,
+    },
+}
+
+#[derive(Debug, Default)]
+pub struct Chunk {
+    pub code: Vec<Instruction>,
+    /// Line and column of each instruction, used for values and errors
+    pub positions: Vec<(usize, usize)>,
+    pub constants: Vec<RuntimeValue>,
+    pub prototypes: Vec<Rc<Prototype>>,
+}
+
+impl Chunk {
+    pub fn emit(&mut self, instruction: Instruction, line: usize, column: usize) -> usize {
+        self.code.push(instruction);
+        self.positions.push((line, column));
+        self.code.len() - 1
+    }
+
+    /// Points the jump at `index` to the next instruction
+    pub fn patch(&mut self, index: usize) {
+        let target = self.code.len();
+
+        match &mut self.code[index] {
+            Instruction::Jump(to)
+            | Instruction::JumpUnless(to)
+            | Instruction::JumpUnlessNull(to)
+            | Instruction::LoadOptionalFunction(_, to)
+            | Instruction::Try(to)
+            | Instruction::BreakIfReturn(to) => *to = target,
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn constant(&mut self, value: RuntimeValue) -> usize {
+        self.constants.push(value);
+        self.constants.len() - 1
+    }
+}
+
+#[derive(Debug)]
+pub struct Prototype {
+    pub name: Symbol,
+    pub arity: usize,
+    /// Position of the name of the function
+    pub line: usize,
+    pub column: usize,
+    /// Names of the parameters and variables of the function, in slot order
+    pub slots: Rc<[Symbol]>,
+    pub body: Body,
+    /// Text of the function's doc comments
+    pub doc: Option<Rc<str>>,
+}
+
+#[derive(Debug)]
+pub enum Body {
+    Compiled(Rc<Chunk>),
+    /// Functions that include files can declare variables the compiler cannot
+    /// know about, so they are left to the interpreter
+    Interpreted(Rc<ast::Function>),
+}
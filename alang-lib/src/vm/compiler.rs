@@ -0,0 +1,523 @@
+use std::rc::Rc;
+
+use crate::{
+    parser::ast::{
+        self,
+        visitor::{self, Visitor},
+        ExpressionType,
+    },
+    symbol::Symbol,
+    types::{
+        boolean::BooleanVal, character::CharacterVal, number::NumberVal, string::StringVal,
+        Logical, Operator, RuntimeValue,
+    },
+};
+
+use super::chunk::{Access, Body, Chunk, Instruction, Prototype};
+
+/// Compiles the program into a chunk that runs on the program's environment
+pub fn compile(program: &ast::Program) -> Chunk {
+    let mut compiler = Compiler {
+        chunk: Chunk::default(),
+        scopes: Vec::new(),
+    };
+
+    compiler.block(&program.body, true, (0, 0));
+    compiler.chunk.emit(Instruction::End, 0, 0);
+
+    compiler.chunk
+}
+
+struct Compiler {
+    chunk: Chunk,
+    // Slot names of the function being compiled and the functions around it,
+    // innermost last. Empty for the program itself.
+    scopes: Vec<Rc<[Symbol]>>,
+}
+
+impl Compiler {
+    fn emit(&mut self, instruction: Instruction, expression: &ast::Expression) -> usize {
+        self.chunk
+            .emit(instruction, expression.line, expression.column)
+    }
+
+    fn access(&self, name: Symbol) -> Access {
+        if self.scopes.is_empty() {
+            return Access::Global(name);
+        }
+
+        for (depth, scope) in self.scopes.iter().rev().enumerate() {
+            if let Some(slot) = scope.iter().position(|known| *known == name) {
+                return match depth {
+                    0 => Access::Local { slot, name },
+                    _ => Access::Captured {
+                        depth: depth - 1,
+                        slot: Some(slot),
+                        name,
+                    },
+                };
+            }
+        }
+
+        Access::Captured {
+            depth: self.scopes.len() - 1,
+            slot: None,
+            name,
+        }
+    }
+
+    /// Leaves the value of the last expression on the stack, or null for an empty block.
+    ///
+    /// Statements are the expressions of a function, of the program or of an `if`
+    /// that is a statement itself. A `return` anywhere else is turned into a value
+    /// that is passed along, to behave exactly like the interpreter.
+    fn block(&mut self, body: &[ast::Expression], statement: bool, position: (usize, usize)) {
+        if body.is_empty() {
+            self.chunk.emit(Instruction::Null, position.0, position.1);
+            return;
+        }
+
+        let mut breaks = Vec::new();
+
+        for (i, expression) in body.iter().enumerate() {
+            match &expression.expression_type {
+                ExpressionType::Return(ret) if statement => {
+                    self.expression(&ret.value, false);
+                    self.emit(Instruction::Return, expression);
+                }
+                _ => {
+                    self.expression(expression, statement);
+
+                    if contains_return(expression) {
+                        let instruction = match statement {
+                            true => Instruction::ExitIfReturn,
+                            false => Instruction::BreakIfReturn(0),
+                        };
+                        let index = self.emit(instruction, expression);
+                        if !statement {
+                            breaks.push(index);
+                        }
+                    }
+                }
+            }
+
+            if i + 1 < body.len() {
+                self.emit(Instruction::Pop, expression);
+            }
+        }
+
+        for index in breaks {
+            self.chunk.patch(index);
+        }
+    }
+
+    fn expression(&mut self, expression: &ast::Expression, statement: bool) {
+        match &expression.expression_type {
+            ExpressionType::Identifier(identifier) => {
+                let access = self.access(identifier.name);
+                self.emit(Instruction::Load(access), expression);
+            }
+            ExpressionType::Number(value) => {
+                self.constant(RuntimeValue::Number(NumberVal::from(*value)), expression)
+            }
+            ExpressionType::String(value) => self.constant(
+                RuntimeValue::String(StringVal::from(value.clone())),
+                expression,
+            ),
+            ExpressionType::Interpolation(interpolation) => {
+                for part in &interpolation.parts {
+                    match part {
+                        ast::InterpolationPart::Text(text) => self.constant(
+                            RuntimeValue::String(StringVal::from(text.clone())),
+                            expression,
+                        ),
+                        ast::InterpolationPart::Expression(part) => self.expression(part, false),
+                    }
+                }
+
+                let count = interpolation.parts.len();
+                self.emit(Instruction::Interpolate(count), expression);
+            }
+            ExpressionType::Character(value) => self.constant(
+                RuntimeValue::Character(CharacterVal::from(*value)),
+                expression,
+            ),
+            ExpressionType::Boolean(value) => {
+                self.constant(RuntimeValue::Boolean(BooleanVal::from(*value)), expression)
+            }
+            ExpressionType::Null => {
+                self.emit(Instruction::Null, expression);
+            }
+            ExpressionType::Type(name) => {
+                self.constant(RuntimeValue::Type(name.as_str().into()), expression)
+            }
+            ExpressionType::Assignment(assignment) => {
+                self.expression(&assignment.right, false);
+
+                match &assignment.left.expression_type {
+                    ExpressionType::Identifier(identifier) => {
+                        let access = self.access(identifier.name);
+                        self.emit(Instruction::Store(access), expression);
+                    }
+                    _ => {
+                        self.emit(Instruction::InvalidAssignment, expression);
+                    }
+                }
+            }
+            ExpressionType::Binary(binary) => {
+                self.expression(&binary.left, false);
+
+                if let Operator::Logical(Logical::Coalesce) = binary.operator {
+                    let to_end = self.emit(Instruction::JumpUnlessNull(0), expression);
+                    self.expression(&binary.right, false);
+                    self.chunk.patch(to_end);
+                    self.emit(Instruction::Reposition, expression);
+                    return;
+                }
+
+                self.expression(&binary.right, false);
+                self.emit(Instruction::Binary(binary.operator.clone()), expression);
+            }
+            ExpressionType::Unary(unary) => {
+                self.expression(&unary.right, false);
+                self.emit(Instruction::Unary(unary.operator.clone()), expression);
+            }
+            ExpressionType::Grouping(grouping) => {
+                self.expression(&grouping.expression, false);
+                self.emit(Instruction::Reposition, expression);
+            }
+            ExpressionType::If(cond) => {
+                let position = (expression.line, expression.column);
+
+                self.expression(&cond.condition, false);
+                let to_else = self.emit(Instruction::JumpUnless(0), expression);
+
+                self.block(&cond.body, statement, position);
+                let to_end = self.emit(Instruction::Jump(0), expression);
+
+                self.chunk.patch(to_else);
+                match &cond.else_body {
+                    Some(else_body) => self.block(else_body, statement, position),
+                    None => {
+                        self.emit(Instruction::Null, expression);
+                    }
+                }
+
+                self.chunk.patch(to_end);
+                self.emit(Instruction::Reposition, expression);
+            }
+            ExpressionType::Function(function) => {
+                let name = match &function.name.expression_type {
+                    ExpressionType::Identifier(identifier) => identifier.name,
+                    _ => unreachable!(),
+                };
+
+                let prototype = self.prototype(name, function);
+                self.chunk.prototypes.push(Rc::new(prototype));
+
+                let index = self.chunk.prototypes.len() - 1;
+                self.emit(Instruction::Function(index), expression);
+
+                let access = self.access(name);
+                self.emit(Instruction::Store(access), expression);
+            }
+            ExpressionType::Return(ret) => {
+                // Only reached for a `return` used as a value
+                self.expression(&ret.value, false);
+                self.emit(Instruction::WrapReturn, expression);
+            }
+            ExpressionType::Call(call) => match &call.name.expression_type {
+                ExpressionType::Identifier(identifier) => {
+                    let access = self.access(identifier.name);
+                    let to_end = if call.optional {
+                        Some(self.emit(Instruction::LoadOptionalFunction(access, 0), &call.name))
+                    } else {
+                        self.emit(Instruction::LoadFunction(access), &call.name);
+                        None
+                    };
+
+                    for argument in &call.parameters {
+                        self.expression(argument, false);
+                    }
+
+                    self.emit(
+                        Instruction::Call {
+                            arguments: call.parameters.len(),
+                            name: identifier.name,
+                        },
+                        &call.name,
+                    );
+
+                    if let Some(to_end) = to_end {
+                        self.chunk.patch(to_end);
+                    }
+                }
+                _ => {
+                    self.emit(Instruction::InvalidFunctionName, &call.name);
+                }
+            },
+            ExpressionType::Member(member) => {
+                self.expression(&member.object, false);
+                self.emit(
+                    Instruction::Member {
+                        name: member.name,
+                        optional: member.optional,
+                    },
+                    expression,
+                );
+            }
+            ExpressionType::Throw(throw) => {
+                self.expression(&throw.value, false);
+                self.emit(Instruction::Throw, expression);
+            }
+            ExpressionType::Try(try_block) => self.try_block(try_block, expression),
+            ExpressionType::Match(matching) => self.match_block(matching, expression, statement),
+            ExpressionType::Include(include) => {
+                self.expression(&include.path, false);
+                self.emit(
+                    Instruction::Include {
+                        line: include.path.line,
+                        column: include.path.column,
+                    },
+                    expression,
+                );
+            }
+        }
+    }
+
+    /// The body and the catch leave their value like the branches of an `if`. An
+    /// error they do not handle goes through a second handler, which runs the
+    /// finally before throwing the error again.
+    fn try_block(&mut self, try_block: &ast::Try, expression: &ast::Expression) {
+        let position = (expression.line, expression.column);
+
+        let to_handler = self.emit(Instruction::Try(0), expression);
+        self.block(&try_block.body, false, position);
+        self.emit(Instruction::EndTry, expression);
+        let to_finally = self.emit(Instruction::Jump(0), expression);
+
+        let to_rethrow = match &try_block.catch {
+            Some(catch) => {
+                self.chunk.patch(to_handler);
+
+                // Without a finally an error in the catch is left to the enclosing handlers
+                let to_rethrow = try_block
+                    .finally
+                    .as_ref()
+                    .map(|_| self.emit(Instruction::Try(0), expression));
+
+                if let ExpressionType::Identifier(identifier) = &catch.name.expression_type {
+                    let access = self.access(identifier.name);
+                    self.emit(Instruction::Store(access), &catch.name);
+                }
+                self.emit(Instruction::Pop, &catch.name);
+
+                self.block(&catch.body, false, position);
+                if to_rethrow.is_some() {
+                    self.emit(Instruction::EndTry, expression);
+                }
+
+                to_rethrow
+            }
+            None => Some(to_handler),
+        };
+
+        self.chunk.patch(to_finally);
+        if let Some(finally) = &try_block.finally {
+            self.block(finally, false, position);
+            self.emit(Instruction::Pop, expression);
+            let to_end = self.emit(Instruction::Jump(0), expression);
+
+            // Only reached with an error on the stack
+            if let Some(to_rethrow) = to_rethrow {
+                self.chunk.patch(to_rethrow);
+            }
+            self.block(finally, false, position);
+            self.emit(Instruction::Pop, expression);
+            self.emit(Instruction::Throw, expression);
+
+            self.chunk.patch(to_end);
+        }
+
+        self.emit(Instruction::Reposition, expression);
+    }
+
+    /// The value stays on the stack while the arms test it, each failed test jumps
+    /// to the next arm and the arm that matches pops it before running its body
+    fn match_block(
+        &mut self,
+        matching: &ast::Match,
+        expression: &ast::Expression,
+        statement: bool,
+    ) {
+        let position = (expression.line, expression.column);
+
+        self.expression(&matching.value, false);
+
+        let mut to_end = Vec::new();
+        for arm in &matching.arms {
+            let mut to_next = Vec::new();
+            let mut bound = false;
+
+            let test = match &arm.pattern {
+                ast::Pattern::Wildcard => None,
+                ast::Pattern::Literal(literal) => {
+                    self.expression(literal, false);
+                    Some(Instruction::MatchLiteral)
+                }
+                ast::Pattern::Range(low, high) => {
+                    self.expression(low, false);
+                    self.expression(high, false);
+                    Some(Instruction::MatchRange)
+                }
+                ast::Pattern::Type(type_name) => {
+                    self.expression(type_name, false);
+                    Some(Instruction::MatchType)
+                }
+                ast::Pattern::Binding(binding) => {
+                    if let ExpressionType::Identifier(identifier) = &binding.expression_type {
+                        let access = self.access(identifier.name);
+                        self.emit(Instruction::Bind(access), binding);
+                        bound = true;
+                    }
+                    None
+                }
+            };
+            if let Some(test) = test {
+                self.chunk.emit(test, arm.line, arm.column);
+                to_next.push(
+                    self.chunk
+                        .emit(Instruction::JumpUnless(0), arm.line, arm.column),
+                );
+            }
+
+            if let Some(guard) = &arm.guard {
+                self.expression(guard, false);
+                to_next.push(self.emit(Instruction::JumpUnless(0), guard));
+            }
+
+            self.emit(Instruction::Pop, expression);
+            self.block(std::slice::from_ref(&*arm.body), statement, position);
+            if bound {
+                self.emit(Instruction::Unbind, expression);
+            }
+            to_end.push(self.emit(Instruction::Jump(0), expression));
+
+            for index in to_next {
+                self.chunk.patch(index);
+            }
+            // The binding only lives for its arm, also when the guard turned it down
+            if bound && arm.guard.is_some() {
+                self.emit(Instruction::Unbind, expression);
+            }
+        }
+
+        self.emit(Instruction::Unmatched, expression);
+
+        for index in to_end {
+            self.chunk.patch(index);
+        }
+        self.emit(Instruction::Reposition, expression);
+    }
+
+    fn constant(&mut self, value: RuntimeValue, expression: &ast::Expression) {
+        let index = self.chunk.constant(value);
+        self.emit(Instruction::Constant(index), expression);
+    }
+
+    fn prototype(&mut self, name: Symbol, function: &ast::Function) -> Prototype {
+        let mut declarations = Declarations::default();
+        for parameter in &function.parameters {
+            declarations.visit_expression(parameter);
+        }
+        declarations.visit_block(&function.body);
+
+        let body = if declarations.include {
+            Body::Interpreted(Rc::new(function.clone()))
+        } else {
+            let mut scopes = self.scopes.clone();
+            scopes.push(declarations.names.clone().into());
+
+            let mut compiler = Compiler {
+                chunk: Chunk::default(),
+                scopes,
+            };
+
+            let end = (function.end_line, 0);
+            compiler.block(&function.body, true, end);
+            compiler.chunk.emit(Instruction::End, end.0, end.1);
+
+            Body::Compiled(Rc::new(compiler.chunk))
+        };
+
+        Prototype {
+            name,
+            arity: function.parameters.len(),
+            line: function.name.line,
+            column: function.name.column,
+            slots: declarations.names.into(),
+            body,
+            doc: function.doc.as_deref().map(Rc::from),
+        }
+    }
+}
+
+// The variables a function declares, starting with its parameters
+#[derive(Default)]
+struct Declarations {
+    names: Vec<Symbol>,
+    include: bool,
+}
+
+impl Declarations {
+    fn declare(&mut self, expression: &ast::Expression) {
+        if let ExpressionType::Identifier(identifier) = &expression.expression_type {
+            if !self.names.contains(&identifier.name) {
+                self.names.push(identifier.name);
+            }
+        }
+    }
+}
+
+impl Visitor for Declarations {
+    fn visit_expression(&mut self, expression: &ast::Expression) {
+        // Parameters
+        self.declare(expression);
+        visitor::walk_expression(self, expression);
+    }
+
+    fn visit_identifier(&mut self, _identifier: &ast::Identifier) {}
+
+    fn visit_assignment(&mut self, assignment: &ast::Assignment) {
+        self.declare(&assignment.left);
+        self.visit_expression(&assignment.right);
+    }
+
+    fn visit_function(&mut self, function: &ast::Function) {
+        // Nested functions get their own variables
+        self.declare(&function.name);
+    }
+
+    fn visit_include(&mut self, include: &ast::Include) {
+        self.include = true;
+        visitor::walk_include(self, include);
+    }
+}
+
+fn contains_return(expression: &ast::Expression) -> bool {
+    #[derive(Default)]
+    struct Returns(bool);
+
+    impl Visitor for Returns {
+        fn visit_return(&mut self, _ret: &ast::Return) {
+            self.0 = true;
+        }
+
+        // A `return` in a nested function belongs to that function
+        fn visit_function(&mut self, _function: &ast::Function) {}
+    }
+
+    let mut returns = Returns::default();
+    returns.visit_expression(expression);
+    returns.0
+}
@@ -0,0 +1,596 @@
+//! A bytecode compiler and stack based virtual machine, an alternative to the
+//! tree walking interpreter that runs programs the same way.
+
+mod chunk;
+mod compiler;
+mod snapshot;
+
+use std::{io::Read, rc::Rc};
+
+use crate::{
+    errors::*,
+    interpreter::{self, Environment},
+    parser::{ast, Parser},
+    symbol::Symbol,
+    types::{
+        boolean::BooleanVal, closure::ClosureVal, error::ErrorVal, function::FunctionVal,
+        string::StringVal, RuntimeType, RuntimeValue,
+    },
+};
+
+pub use chunk::{Access, Body, Chunk, Instruction, Prototype};
+pub use compiler::compile;
+pub use snapshot::Snapshot;
+
+/// Like `interpreter::run`, returns the value of a top level `return`, or null
+pub fn run(program: &ast::Program, env: &mut Environment) -> Result<RuntimeValue, Error> {
+    let (value, returned) = execute(compile(program), env)?;
+
+    match returned {
+        true => Ok(value.value),
+        false => Ok(RuntimeValue::Null),
+    }
+}
+
+/// Like `interpreter::evaluate`, returns the value of the last expression
+/// or the returned value
+pub fn evaluate(program: &ast::Program, env: &mut Environment) -> Result<RuntimeType, Error> {
+    Ok(execute(compile(program), env)?.0)
+}
+
+/// Calls a compiled function, `name` is the name it was called by and is used in errors
+pub fn call(
+    name: &str,
+    closure: &ClosureVal,
+    args: Vec<RuntimeType>,
+    line: usize,
+    column: usize,
+) -> Result<RuntimeType, Error> {
+    if closure.arity() != args.len() {
+        return Err(
+            InvalidArgumentCount::new(name, args.len(), closure.arity(), line, column).into(),
+        );
+    }
+
+    // Functions only see the variables of the program through their snapshot
+    let mut env = Environment::new();
+    let mut vm = Vm {
+        stack: Vec::new(),
+        frames: Vec::new(),
+        env: &mut env,
+    };
+
+    vm.enter(closure, args, (line, column));
+    Ok(vm.run()?.0)
+}
+
+fn execute(chunk: Chunk, env: &mut Environment) -> Result<(RuntimeType, bool), Error> {
+    let mut vm = Vm {
+        stack: Vec::new(),
+        frames: vec![Frame {
+            chunk: Rc::new(chunk),
+            ip: 0,
+            slots: Vec::new(),
+            names: Rc::from([]),
+            snapshot: None,
+            base: 0,
+            call: (0, 0),
+            handlers: Vec::new(),
+            bindings: Vec::new(),
+        }],
+        env,
+    };
+
+    vm.run()
+}
+
+struct Frame {
+    chunk: Rc<Chunk>,
+    ip: usize,
+    slots: Vec<Option<RuntimeValue>>,
+    names: Rc<[Symbol]>,
+    /// What the function could see when it was created, `None` for the program
+    snapshot: Option<Rc<Snapshot>>,
+    /// Height of the stack when the frame was entered
+    base: usize,
+    /// Position of the call, which the function's value takes when it does not return
+    call: (usize, usize),
+    /// The `try` blocks the frame is in, innermost last
+    handlers: Vec<Handler>,
+    /// What the bindings of the `match` arms the frame is in shadowed, innermost last
+    bindings: Vec<(Access, Option<RuntimeValue>)>,
+}
+
+struct Handler {
+    target: usize,
+    /// Height of the stack when the `try` started
+    height: usize,
+    /// Number of bindings of the frame when the `try` started
+    bindings: usize,
+}
+
+struct Vm<'a> {
+    stack: Vec<RuntimeType>,
+    frames: Vec<Frame>,
+    env: &'a mut Environment,
+}
+
+impl Vm<'_> {
+    fn enter(&mut self, closure: &ClosureVal, args: Vec<RuntimeType>, call: (usize, usize)) {
+        let chunk = match &closure.prototype.body {
+            Body::Compiled(chunk) => chunk.clone(),
+            Body::Interpreted(_) => unreachable!(),
+        };
+
+        let mut slots = vec![None; closure.prototype.slots.len()];
+        for (slot, arg) in slots.iter_mut().zip(args) {
+            *slot = Some(arg.value);
+        }
+
+        self.frames.push(Frame {
+            chunk,
+            ip: 0,
+            slots,
+            names: closure.prototype.slots.clone(),
+            snapshot: Some(closure.snapshot.clone()),
+            base: self.stack.len(),
+            call,
+            handlers: Vec::new(),
+            bindings: Vec::new(),
+        });
+    }
+
+    fn frame(&mut self) -> &mut Frame {
+        self.frames.last_mut().unwrap()
+    }
+
+    fn pop(&mut self) -> RuntimeType {
+        self.stack.pop().unwrap()
+    }
+
+    fn push_boolean(&mut self, value: bool, line: usize, column: usize) {
+        self.stack.push(RuntimeType {
+            value: RuntimeValue::Boolean(BooleanVal::from(value)),
+            line,
+            column,
+        });
+    }
+
+    /// Leaves the running frame with its result, returning the result and whether
+    /// it was returned when this was the last frame
+    fn leave(&mut self, value: RuntimeType, returned: bool) -> Option<(RuntimeType, bool)> {
+        self.unbind(0);
+        let frame = self.frames.pop().unwrap();
+        self.stack.truncate(frame.base);
+
+        if self.frames.is_empty() {
+            return Some((value, returned));
+        }
+
+        self.stack.push(value);
+        None
+    }
+
+    fn load(&mut self, access: Access) -> Option<RuntimeValue> {
+        let frame = self.frames.last().unwrap();
+
+        match access {
+            Access::Global(name) => self.env.lookup(name).cloned(),
+            Access::Local { slot, name } => match &frame.slots[slot] {
+                Some(value) => Some(value.clone()),
+                None => frame.snapshot.as_ref()?.lookup(name),
+            },
+            Access::Captured { depth, slot, name } => {
+                frame.snapshot.as_ref()?.at(depth).get(slot, name)
+            }
+        }
+    }
+
+    /// The variables a function created by the running frame gets to see
+    fn snapshot(&self) -> Snapshot {
+        let frame = self.frames.last().unwrap();
+
+        match &frame.snapshot {
+            Some(parent) => Snapshot::Frame {
+                names: frame.names.clone(),
+                slots: frame.slots.clone(),
+                parent: parent.clone(),
+            },
+            None => Snapshot::Globals(self.env.clone()),
+        }
+    }
+
+    fn run(&mut self) -> Result<(RuntimeType, bool), Error> {
+        loop {
+            match self.dispatch() {
+                Err(error) => self.catch(error)?,
+                result => return result,
+            }
+        }
+    }
+
+    /// Unwinds to the innermost `try` of the running frames and continues at its
+    /// handler, or gives the error back when there is none
+    fn catch(&mut self, error: Error) -> Result<(), Error> {
+        if self.frames.iter().all(|frame| frame.handlers.is_empty()) {
+            while self.frames.len() > 1 {
+                self.unbind(0);
+                self.frames.pop();
+            }
+            self.unbind(0);
+            return Err(error);
+        }
+
+        loop {
+            if let Some(handler) = self.frame().handlers.pop() {
+                self.unbind(handler.bindings);
+                self.stack.truncate(handler.height);
+                self.stack.push(RuntimeType {
+                    value: RuntimeValue::Error(ErrorVal::from(&error)),
+                    line: error.line(),
+                    column: error.column(),
+                });
+                self.frame().ip = handler.target;
+                return Ok(());
+            }
+
+            self.unbind(0);
+            let frame = self.frames.pop().unwrap();
+            self.stack.truncate(frame.base);
+        }
+    }
+
+    /// Puts back what the bindings of the running frame shadowed, down to `height` of them
+    fn unbind(&mut self, height: usize) {
+        while self.frame().bindings.len() > height {
+            let (access, shadowed) = self.frame().bindings.pop().unwrap();
+            self.restore(access, shadowed);
+        }
+    }
+
+    fn restore(&mut self, access: Access, value: Option<RuntimeValue>) {
+        match (access, value) {
+            (Access::Global(name), Some(value)) => {
+                self.env.variables.insert(name, value);
+            }
+            (Access::Global(name), None) => {
+                self.env.variables.remove(&name);
+            }
+            (Access::Local { slot, .. }, value) => self.frame().slots[slot] = value,
+            // Bindings always declare a variable of the running function
+            (Access::Captured { .. }, _) => unreachable!(),
+        }
+    }
+
+    fn dispatch(&mut self) -> Result<(RuntimeType, bool), Error> {
+        loop {
+            let frame = self.frame();
+            let chunk = frame.chunk.clone();
+            let ip = frame.ip;
+            frame.ip += 1;
+
+            let (line, column) = chunk.positions[ip];
+
+            match &chunk.code[ip] {
+                Instruction::Constant(index) => self.stack.push(RuntimeType {
+                    value: chunk.constants[*index].clone(),
+                    line,
+                    column,
+                }),
+                Instruction::Null => self.stack.push(RuntimeType {
+                    value: RuntimeValue::Null,
+                    line,
+                    column,
+                }),
+                Instruction::Load(access) => {
+                    let value = self.load(*access).ok_or_else(|| {
+                        UndefinedVariable::new(name(access).to_string(), line, column).into()
+                    })?;
+
+                    self.stack.push(RuntimeType {
+                        value,
+                        line,
+                        column,
+                    });
+                }
+                instruction @ (Instruction::LoadFunction(access)
+                | Instruction::LoadOptionalFunction(access, _)) => {
+                    let value = self.load(*access).ok_or_else(|| {
+                        UndefinedFunction::new(name(access).to_string(), line, column).into()
+                    })?;
+
+                    if let (RuntimeValue::Null, Instruction::LoadOptionalFunction(_, target)) =
+                        (&value, instruction)
+                    {
+                        self.stack.push(RuntimeType {
+                            value,
+                            line,
+                            column,
+                        });
+                        self.frame().ip = *target;
+                        continue;
+                    }
+
+                    if !matches!(
+                        value,
+                        RuntimeValue::Function(_)
+                            | RuntimeValue::NativeFunction(_)
+                            | RuntimeValue::Closure(_)
+                    ) {
+                        return Err(InvalidFunctionName::new(line, column).into());
+                    }
+
+                    self.stack.push(RuntimeType {
+                        value,
+                        line,
+                        column,
+                    });
+                }
+                Instruction::Store(access) => {
+                    let value = self.stack.last().unwrap().value.clone();
+
+                    match *access {
+                        Access::Global(name) => {
+                            self.env.set(name, value, false);
+                        }
+                        Access::Local { slot, .. } => self.frame().slots[slot] = Some(value),
+                        // Assignments always declare a variable of the running function
+                        Access::Captured { .. } => unreachable!(),
+                    }
+                }
+                Instruction::Bind(access) => {
+                    let value = self.stack.last().unwrap().value.clone();
+
+                    let shadowed = match *access {
+                        Access::Global(name) => self.env.variables.insert(name, value),
+                        Access::Local { slot, .. } => self.frame().slots[slot].replace(value),
+                        Access::Captured { .. } => unreachable!(),
+                    };
+                    self.frame().bindings.push((*access, shadowed));
+                }
+                Instruction::Unbind => {
+                    let (access, shadowed) = self.frame().bindings.pop().unwrap();
+                    self.restore(access, shadowed);
+                }
+                Instruction::Pop => {
+                    self.pop();
+                }
+                Instruction::Binary(operator) => {
+                    let right = self.pop();
+                    let left = self.pop();
+
+                    let value = interpreter::binary_operation(left, right, operator, line, column)?;
+                    self.stack.push(value);
+                }
+                Instruction::Unary(operator) => {
+                    let value = self.pop();
+
+                    let value = interpreter::unary_operation(value, operator, line, column)?;
+                    self.stack.push(value);
+                }
+                Instruction::Interpolate(count) => {
+                    let parts = self.stack.split_off(self.stack.len() - count);
+                    let string: String = parts.iter().map(ToString::to_string).collect();
+
+                    self.stack.push(RuntimeType {
+                        value: RuntimeValue::String(StringVal::from(string)),
+                        line,
+                        column,
+                    });
+                }
+                Instruction::JumpUnless(target) => {
+                    let condition = self.pop();
+
+                    match &condition.value {
+                        RuntimeValue::Boolean(value) if value.value => {}
+                        RuntimeValue::Boolean(_) => self.frame().ip = *target,
+                        _ => return Err(InvalidCondition::new(condition, line, column).into()),
+                    }
+                }
+                Instruction::Jump(target) => self.frame().ip = *target,
+                Instruction::JumpUnlessNull(target) => {
+                    if let Some(RuntimeValue::Null) = self.stack.last().map(|value| &value.value) {
+                        self.pop();
+                    } else {
+                        self.frame().ip = *target;
+                    }
+                }
+                Instruction::Reposition => {
+                    let value = self.stack.last_mut().unwrap();
+                    value.line = line;
+                    value.column = column;
+                }
+                Instruction::Function(index) => {
+                    let prototype = chunk.prototypes[*index].clone();
+
+                    let value = match &prototype.body {
+                        Body::Compiled(_) => RuntimeValue::Closure(ClosureVal::new(
+                            prototype.clone(),
+                            Rc::new(self.snapshot()),
+                        )),
+                        Body::Interpreted(declaration) => RuntimeValue::Function(FunctionVal::new(
+                            declaration.clone(),
+                            Environment::new_with_parent(self.snapshot().to_environment()),
+                        )),
+                    };
+
+                    self.stack.push(RuntimeType {
+                        value,
+                        line,
+                        column,
+                    });
+                }
+                Instruction::Call { arguments, name } => {
+                    let args = self.stack.split_off(self.stack.len() - arguments);
+                    let function = self.pop();
+
+                    match &function.value {
+                        RuntimeValue::Closure(closure) => {
+                            if closure.arity() != args.len() {
+                                return Err(InvalidArgumentCount::new(
+                                    name.as_str(),
+                                    args.len(),
+                                    closure.arity(),
+                                    line,
+                                    column,
+                                )
+                                .into());
+                            }
+
+                            self.enter(closure, args, (line, column));
+                        }
+                        value => {
+                            let value = interpreter::call_function(
+                                name.as_str(),
+                                value,
+                                args,
+                                line,
+                                column,
+                            )?;
+                            self.stack.push(value);
+                        }
+                    }
+                }
+                Instruction::Return => {
+                    let value = self.pop();
+
+                    if let Some(result) = self.leave(value, true) {
+                        return Ok(result);
+                    }
+                }
+                Instruction::WrapReturn => {
+                    let value = self.pop();
+
+                    self.stack.push(RuntimeType {
+                        value: RuntimeValue::Return(Box::new(value)),
+                        line,
+                        column,
+                    });
+                }
+                Instruction::ExitIfReturn => {
+                    if let RuntimeValue::Return(_) = self.stack.last().unwrap().value {
+                        let value = match self.pop().value {
+                            RuntimeValue::Return(value) => *value,
+                            _ => unreachable!(),
+                        };
+
+                        if let Some(result) = self.leave(value, true) {
+                            return Ok(result);
+                        }
+                    }
+                }
+                Instruction::BreakIfReturn(target) => {
+                    if let RuntimeValue::Return(_) = self.stack.last().unwrap().value {
+                        self.frame().ip = *target;
+                    }
+                }
+                Instruction::Member { name, optional } => {
+                    let object = self.pop();
+
+                    let value = interpreter::member_access(object, *name, *optional, line, column)?;
+                    self.stack.push(value);
+                }
+                Instruction::Throw => {
+                    let value = self.pop();
+                    return Err(Thrown::new(value, line, column).into());
+                }
+                Instruction::Try(target) => {
+                    let handler = Handler {
+                        target: *target,
+                        height: self.stack.len(),
+                        bindings: self.frame().bindings.len(),
+                    };
+                    self.frame().handlers.push(handler);
+                }
+                Instruction::EndTry => {
+                    self.frame().handlers.pop();
+                }
+                Instruction::MatchLiteral => {
+                    let literal = self.pop();
+                    let value = &self.stack.last().unwrap().value;
+
+                    let matched = interpreter::matches_literal(value, &literal.value);
+                    self.push_boolean(matched, line, column);
+                }
+                Instruction::MatchRange => {
+                    let high = self.pop();
+                    let low = self.pop();
+                    let value = self.stack.last().unwrap();
+
+                    let matched = interpreter::matches_range(value, &low, &high, line, column)?;
+                    self.push_boolean(matched, line, column);
+                }
+                Instruction::MatchType => {
+                    let type_name = self.pop();
+                    let value = self.stack.last().unwrap().clone();
+
+                    let matched = interpreter::matches_type(value, type_name, line, column)?;
+                    self.push_boolean(matched, line, column);
+                }
+                Instruction::Unmatched => {
+                    let value = self.pop();
+                    return Err(UnmatchedValue::new(value, line, column).into());
+                }
+                Instruction::Include {
+                    line: path_line,
+                    column: path_column,
+                } => {
+                    let path = self.pop();
+                    let value = self.include(path, (*path_line, *path_column), (line, column))?;
+                    self.stack.push(value);
+                }
+                Instruction::InvalidAssignment => {
+                    return Err(InvalidAssignment::new(line, column).into())
+                }
+                Instruction::InvalidFunctionName => {
+                    return Err(InvalidFunctionName::new(line, column).into())
+                }
+                Instruction::End => {
+                    let mut value = self.pop();
+
+                    // Functions that do not return take the position of the call
+                    if self.frame().snapshot.is_some() {
+                        (value.line, value.column) = self.frame().call;
+                    }
+
+                    if let Some(result) = self.leave(value, false) {
+                        return Ok(result);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Runs the file in the program's environment, like the interpreter
+    fn include(
+        &mut self,
+        path: RuntimeType,
+        (path_line, path_column): (usize, usize),
+        (line, column): (usize, usize),
+    ) -> Result<RuntimeType, Error> {
+        let file = match &path.value {
+            RuntimeValue::String(file) => file.value.clone(),
+            _ => return Err(InvalidIncludePath::new(path, line, column).into()),
+        };
+
+        let mut source_code = String::new();
+        std::fs::File::open(&*file)
+            .and_then(|mut file| file.read_to_string(&mut source_code))
+            .map_err(|e| IOError::new(e, path_line, path_column).into())?;
+
+        let mut ast = Parser::produce_ast(&source_code)?;
+        interpreter::resolve(&mut ast, self.env)?;
+
+        Ok(RuntimeType {
+            value: run(&ast, self.env)?,
+            line,
+            column,
+        })
+    }
+}
+
+fn name(access: &Access) -> Symbol {
+    match *access {
+        Access::Global(name) | Access::Local { name, .. } | Access::Captured { name, .. } => name,
+    }
+}
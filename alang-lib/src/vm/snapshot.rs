@@ -0,0 +1,73 @@
+use std::rc::Rc;
+
+use crate::{interpreter::Environment, symbol::Symbol, types::RuntimeValue};
+
+/// The variables a function could see when it was created. Like the interpreter,
+/// functions keep a copy instead of a reference, so later changes are not seen.
+#[derive(Debug)]
+pub enum Snapshot {
+    /// The variables of the program
+    Globals(Environment),
+    /// The variables of a running function, `names` are the names of its slots
+    Frame {
+        names: Rc<[Symbol]>,
+        slots: Vec<Option<RuntimeValue>>,
+        parent: Rc<Snapshot>,
+    },
+}
+
+impl Snapshot {
+    /// The snapshot of the function `depth` levels out
+    pub fn at(&self, depth: usize) -> &Snapshot {
+        match (depth, self) {
+            (0, _) | (_, Snapshot::Globals(_)) => self,
+            (_, Snapshot::Frame { parent, .. }) => parent.at(depth - 1),
+        }
+    }
+
+    /// A variable read from the slot of this snapshot, searching outwards
+    /// by name when it was not set yet
+    pub fn get(&self, slot: Option<usize>, name: Symbol) -> Option<RuntimeValue> {
+        match (slot, self) {
+            (Some(slot), Snapshot::Frame { slots, parent, .. }) => match &slots[slot] {
+                Some(value) => Some(value.clone()),
+                None => parent.lookup(name),
+            },
+            _ => self.lookup(name),
+        }
+    }
+
+    pub fn lookup(&self, name: Symbol) -> Option<RuntimeValue> {
+        match self {
+            Snapshot::Globals(env) => env.lookup(name).cloned(),
+            Snapshot::Frame {
+                names,
+                slots,
+                parent,
+            } => match names.iter().position(|known| *known == name) {
+                Some(slot) if slots[slot].is_some() => slots[slot].clone(),
+                _ => parent.lookup(name),
+            },
+        }
+    }
+
+    /// The same variables as an interpreter environment
+    pub fn to_environment(&self) -> Environment {
+        match self {
+            Snapshot::Globals(env) => env.clone(),
+            Snapshot::Frame {
+                names,
+                slots,
+                parent,
+            } => {
+                let mut env = Environment::new_with_parent(parent.to_environment());
+                for (name, value) in names.iter().zip(slots) {
+                    if let Some(value) = value {
+                        env.set(*name, value.clone(), false);
+                    }
+                }
+                env
+            }
+        }
+    }
+}
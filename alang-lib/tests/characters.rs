@@ -0,0 +1,67 @@
+use alang_lib::{
+    interpreter::{self, Environment},
+    parser::Parser,
+};
+
+/// The value of the last expression of `source`, as the REPL would show it
+fn evaluate(source: &str) -> String {
+    let mut env = Environment::new().with_default_scope();
+    let program = Parser::produce_ast(source).unwrap();
+
+    interpreter::evaluate(&program, &mut env)
+        .unwrap()
+        .value
+        .to_string()
+}
+
+#[test]
+fn is_digit_accepts_ascii_digits() {
+    for digit in '0'..='9' {
+        assert_eq!(evaluate(&format!("is_digit('{}')", digit)), "true");
+    }
+}
+
+#[test]
+fn is_digit_rejects_other_numeric_characters() {
+    // Fractions, superscripts, Roman numerals and digits of other scripts
+    for numeric in ['½', '²', 'Ⅻ', '٣'] {
+        assert_eq!(
+            evaluate(&format!("is_digit('{}')", numeric)),
+            "false",
+            "{} is not a digit",
+            numeric
+        );
+    }
+}
+
+#[test]
+fn characters_are_ordered_by_code_point() {
+    let cases = [
+        ("'a' < 'b'", "true"),
+        ("'b' < 'a'", "false"),
+        ("'b' <= 'b'", "true"),
+        ("'c' <= 'b'", "false"),
+        ("'z' > 'q'", "true"),
+        ("'q' >= 'q'", "true"),
+        ("'Z' < 'a'", "true"),
+        ("'é' > 'z'", "true"),
+    ];
+
+    for (source, expected) in cases {
+        assert_eq!(evaluate(source), expected, "{}", source);
+    }
+}
+
+#[test]
+fn characters_in_range_patterns() {
+    let source = "function kind(c)
+                    match c with
+                      'a'..'z' => \"lower\"
+                      'A'..'Z' => \"upper\"
+                      _ => \"other\"
+                    end
+                  end
+                  \"${kind('q')} ${kind('Q')} ${kind('1')}\"";
+
+    assert_eq!(evaluate(source), "lower upper other");
+}
@@ -0,0 +1,84 @@
+//! A `return` inside an `if` ends the function, it does not only end the `if`.
+//! The interpreter and the virtual machine have to agree on this.
+
+use alang_lib::{
+    interpreter::{self, Environment},
+    parser::Parser,
+    vm,
+};
+
+fn run(source: &str, with_vm: bool) -> String {
+    let mut env = Environment::new().with_default_scope();
+    let mut program = Parser::produce_ast(source).unwrap();
+    interpreter::resolve(&mut program, &env).unwrap();
+
+    let value = match with_vm {
+        true => vm::run(&program, &mut env),
+        false => interpreter::run(&program, &mut env),
+    };
+
+    value.unwrap().to_string()
+}
+
+fn assert_returns(source: &str, expected: &str) {
+    assert_eq!(run(source, false), expected, "interpreter");
+    assert_eq!(run(source, true), expected, "vm");
+}
+
+#[test]
+fn return_in_the_body_of_an_if() {
+    assert_returns(
+        "function f()
+           if true then
+             return 1
+           end
+           2
+         end
+         return f()",
+        "1",
+    );
+}
+
+#[test]
+fn return_in_the_else_branch() {
+    assert_returns(
+        "function f(x)
+           if x then
+             return 1
+           else
+             return 3
+           end
+           2
+         end
+         return f(true) * 10 + f(false)",
+        "13",
+    );
+}
+
+#[test]
+fn return_in_a_nested_if() {
+    assert_returns(
+        "function f(x)
+           if x > 0 then
+             if x > 10 then
+               return \"big\"
+             end
+             \"ignored\"
+           end
+           \"small\"
+         end
+         return \"${f(50)} ${f(5)}\"",
+        "big small",
+    );
+}
+
+#[test]
+fn return_in_an_if_at_the_top_level() {
+    assert_returns(
+        "if true then
+           return 1
+         end
+         return 2",
+        "1",
+    );
+}
@@ -0,0 +1,27 @@
+use alang_lib::formatter::{format, FormatOptions};
+
+fn formatted(source: &str) -> String {
+    format(source, &FormatOptions::default()).unwrap()
+}
+
+#[test]
+fn multi_line_try_stays_on_its_lines() {
+    let source = "\
+t = try
+    throw \"x\"
+catch e
+    e.message
+finally
+    q = 1
+end
+";
+
+    assert_eq!(formatted(source), source);
+}
+
+#[test]
+fn single_line_try_stays_on_one_line() {
+    let source = "t = try throw \"x\" catch e e end\n";
+
+    assert_eq!(formatted(source), source);
+}
@@ -0,0 +1,25 @@
+use alang_lib::json::{parse, Json};
+
+#[test]
+fn numbers_out_of_range_are_rejected() {
+    for source in ["1e999999", "-1e999999", "[1, 1e400]"] {
+        assert!(parse(source).is_err(), "{} should not parse", source);
+    }
+}
+
+#[test]
+fn numbers_json_cannot_hold_are_written_as_strings() {
+    assert_eq!(Json::Number(f64::INFINITY).to_string(), "\"Infinity\"");
+    assert_eq!(Json::Number(f64::NEG_INFINITY).to_string(), "\"-Infinity\"");
+    assert_eq!(Json::Number(f64::NAN).to_string(), "\"NaN\"");
+    assert_eq!(Json::Number(1.5).to_string(), "1.5");
+}
+
+#[test]
+fn written_numbers_read_back() {
+    let json = Json::Array(vec![Json::Number(1e300), Json::Number(f64::INFINITY)]);
+
+    let read = parse(&json.to_string()).unwrap();
+    assert_eq!(read.as_array().unwrap()[0].as_f64().unwrap(), 1e300);
+    assert_eq!(read.as_array().unwrap()[1].as_f64().unwrap(), f64::INFINITY);
+}
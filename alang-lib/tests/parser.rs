@@ -0,0 +1,39 @@
+use alang_lib::parser::{ast::ExpressionType, Parser};
+
+/// `///` only documents a declaration where a statement starts, anywhere else
+/// it is an ordinary comment
+#[test]
+fn doc_comments_outside_statements_are_comments() {
+    let sources = [
+        "x = f(
+           /// inside brackets
+           1)",
+        "x = 1 +
+           /// after an operator
+           2",
+        "x = match 2 with
+           1 => \"one\"
+           /// between arms
+           2 => \"two\"
+           /// before the end
+         end",
+    ];
+
+    for source in sources {
+        if let Err(error) = Parser::produce_ast(source) {
+            panic!("{:?} does not parse: {}", source, error);
+        }
+    }
+}
+
+#[test]
+fn doc_comments_document_declarations() {
+    let program = Parser::produce_ast("/// The answer\nanswer = 42").unwrap();
+
+    match &program.body[0].expression_type {
+        ExpressionType::Assignment(assignment) => {
+            assert_eq!(assignment.doc.as_deref(), Some("The answer"));
+        }
+        other => panic!("expected an assignment, got {:?}", other),
+    }
+}
@@ -0,0 +1,233 @@
+//! Runs the same scripts through the interpreter and the virtual machine and
+//! checks that they agree on the value, or on the error and where it happened.
+
+use alang_lib::{
+    interpreter::{self, Environment},
+    parser::Parser,
+    vm,
+};
+
+const SCRIPTS: &[(&str, &str)] = &[
+    (
+        "calls",
+        "function add(a, b)
+           a + b
+         end
+         function twice(x)
+           add(x, x)
+         end
+         return twice(add(1, 2))",
+    ),
+    (
+        "early return",
+        "function sign(x)
+           if x < 0 then
+             return -1
+           end
+           1
+         end
+         return sign(-5) * 10 + sign(5)",
+    ),
+    (
+        "wrong argument count",
+        "function add(a, b)
+           a + b
+         end
+         add(1)",
+    ),
+    (
+        "error inside a call",
+        "function broken(x)
+           x + true
+         end
+         broken(1)",
+    ),
+    (
+        "closures",
+        "function counter(start)
+           count = start
+           function next(by)
+             count + by
+           end
+           next
+         end
+         step = counter(10)
+         return step(5)",
+    ),
+    (
+        "closures keep their own copy",
+        "function make(x)
+           function add(y)
+             x + y
+           end
+           add
+         end
+         one = make(1)
+         ten = make(10)
+         return one(2) * 100 + ten(2)",
+    ),
+    (
+        "try catches a throw",
+        "return try
+           throw \"boom\"
+         catch e
+           e
+         end",
+    ),
+    (
+        "try unwinds across frames",
+        "function inner(x)
+           throw \"deep ${x}\"
+         end
+         function outer(x)
+           inner(x + 1)
+         end
+         return try
+           outer(1)
+         catch e
+           e
+         end",
+    ),
+    (
+        "try catches an operation error",
+        "function bad()
+           1 + true
+         end
+         return try
+           bad()
+         catch e
+           e
+         end",
+    ),
+    (
+        "uncaught throw",
+        "function fail()
+           throw \"nobody catches this\"
+         end
+         fail()",
+    ),
+    (
+        "match arms",
+        "function describe(x)
+           match x with
+             0 => \"zero\"
+             1..9 => \"digit\"
+             is string => \"text\"
+             n when n < 0 => \"negative ${n}\"
+             _ => \"big\"
+           end
+         end
+         return \"${describe(0)} ${describe(4)} ${describe(\"a\")} ${describe(-2)} ${describe(50)}\"",
+    ),
+    (
+        "match bindings stay in their arm",
+        "n = 100
+         r = match 5 with n when n > 10 => \"big\"; _ => \"small\" end
+         t = try
+           match 7 with n => throw \"boom ${n}\" end
+         catch e
+           e
+         end
+         return \"${n} ${r} ${t}\"",
+    ),
+    (
+        "character ranges",
+        "return match 'q' with 'a'..'z' => \"lower\"; _ => \"other\" end",
+    ),
+    ("unmatched value", "match 3 with 1 => \"one\" end"),
+    (
+        "range that cannot be compared",
+        "match true with false..true => \"boolean\" end",
+    ),
+];
+
+/// Runs `source` with the interpreter or the virtual machine, giving back the
+/// value or the error with its position
+fn run(source: &str, with_vm: bool) -> Result<String, (String, usize, usize)> {
+    let mut env = Environment::new().with_default_scope();
+    let mut program = Parser::produce_ast(source).expect("script should parse");
+    interpreter::resolve(&mut program, &env).expect("script should resolve");
+
+    let result = match with_vm {
+        true => vm::run(&program, &mut env),
+        false => interpreter::run(&program, &mut env),
+    };
+
+    result
+        .map(|value| format!("{:?}", value))
+        .map_err(|error| (error.message().to_string(), error.line(), error.column()))
+}
+
+fn assert_same(name: &str, source: &str) {
+    let interpreted = run(source, false);
+    let compiled = run(source, true);
+
+    assert_eq!(interpreted, compiled, "{} runs differently on the vm", name);
+}
+
+#[test]
+fn vm_matches_interpreter() {
+    for (name, source) in SCRIPTS {
+        assert_same(name, source);
+    }
+}
+
+#[test]
+fn vm_matches_interpreter_with_include() {
+    let file = std::env::temp_dir().join(format!("alang-vm-parity-{}.a", std::process::id()));
+    std::fs::write(
+        &file,
+        "included = 5
+         function helper(x)
+           if x > 1 then
+             throw \"too big ${x}\"
+           end
+           x * included
+         end
+         return included * 2",
+    )
+    .unwrap();
+    let path = file.to_str().unwrap().replace('\\', "\\\\");
+
+    let scripts = [
+        (
+            "include",
+            format!(
+                "r = include(\"{path}\")
+                 return r + helper(1) * 100",
+            ),
+        ),
+        (
+            "include in a function",
+            format!(
+                "function load()
+                   include(\"{path}\")
+                   included + 1
+                 end
+                 return load()",
+            ),
+        ),
+        (
+            "error from an included function",
+            format!(
+                "include(\"{path}\")
+                 t = try
+                   helper(2)
+                 catch e
+                   e
+                 end
+                 helper(3)",
+            ),
+        ),
+        (
+            "missing include",
+            "include(\"/nonexistent/file.a\")".to_string(),
+        ),
+    ];
+
+    for (name, source) in &scripts {
+        assert_same(name, source);
+    }
+
+    std::fs::remove_file(file).unwrap();
+}
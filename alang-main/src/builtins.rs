@@ -0,0 +1,77 @@
+use std::io::Write;
+
+use alang_lib::{
+    errors::{InvalidArgumentCount, InvalidArgumentType},
+    interpreter::Environment,
+    types::{
+        native_function::NativeFunctionVal, number::NumberVal, string::StringVal, RuntimeType,
+        RuntimeValue,
+    },
+};
+
+/// Creates the global environment for scripts started from the command line
+pub fn environment(script_args: Vec<String>) -> Environment {
+    let mut env = Environment::new().with_default_scope();
+    install(&mut env, script_args);
+    env
+}
+
+/// Installs the process related built-ins:
+///
+/// - `args()` returns the number of script arguments, `args(i)` the i-th argument or null
+/// - `env_var(name)` returns the environment variable or null when it is not set
+/// - `exit(code)` stops the script and exits the process with `code`, from 0 to 255
+pub fn install(env: &mut Environment, script_args: Vec<String>) {
+    let args = NativeFunctionVal::new("args", None, move |args| match args.as_slice() {
+        [] => Ok(RuntimeValue::Number(NumberVal::from(
+            script_args.len() as f64
+        ))),
+        [index] => {
+            let index = match &index.value {
+                RuntimeValue::Number(index) if index.value() >= 0.0 => index.value() as usize,
+                _ => return Err(InvalidArgumentType::new("args", index.clone(), "number").into()),
+            };
+
+            Ok(match script_args.get(index) {
+                Some(arg) => RuntimeValue::String(StringVal::from(arg.clone())),
+                None => RuntimeValue::Null,
+            })
+        }
+        [first, ..] => {
+            Err(InvalidArgumentCount::new("args", args.len(), 1, first.line, first.column).into())
+        }
+    });
+
+    let env_var = NativeFunctionVal::new("env_var", Some(1), |args| {
+        let name = string_argument("env_var", &args[0])?;
+
+        Ok(match std::env::var(name) {
+            Ok(value) => RuntimeValue::String(StringVal::from(value)),
+            Err(_) => RuntimeValue::Null,
+        })
+    });
+
+    let exit = NativeFunctionVal::new("exit", Some(1), |args| {
+        let code = match &args[0].value {
+            RuntimeValue::Number(code) => crate::exit_code(code.value()),
+            _ => return Err(InvalidArgumentType::new("exit", args[0].clone(), "number").into()),
+        };
+
+        let _ = std::io::stdout().flush();
+        std::process::exit(code);
+    });
+
+    for function in [args, env_var, exit] {
+        env.set(function.name, RuntimeValue::NativeFunction(function), false);
+    }
+}
+
+fn string_argument<'a>(
+    name: &str,
+    argument: &'a RuntimeType,
+) -> Result<&'a str, alang_lib::errors::Error> {
+    match &argument.value {
+        RuntimeValue::String(value) => Ok(value.value()),
+        _ => Err(InvalidArgumentType::new(name, argument.clone(), "string").into()),
+    }
+}
@@ -0,0 +1,241 @@
+use std::{fmt::Display, iter::Peekable};
+
+pub const USAGE: &str = "\
+Usage: alang-main [options] [command] [arguments]
+
+Commands:
+    run <file> [args]   Run a script, passing it the arguments
+    tokens <file>       Print the tokens of a script
+    ast <file>          Print the syntax tree of a script
+    check <file>        Parse a script and check its names without running it
+    eval [-e <code>]    Run code from the argument, or from stdin
+    fmt <files>         Format scripts in place
+        --check         Only report the files that are not formatted
+        --width <n>     Maximum line width, 100 by default
+    lint <files>        Report likely mistakes in scripts
+        --config <file> Rules to enable or disable, by default the nearest
+                        .alanglint file in the script's directory or above
+    doc <file>          Print Markdown API docs for a script and the files
+                        it includes, from their `///` comments
+        --html          Print an HTML page instead
+    repl                Start the interactive REPL (default)
+    help                Show this message
+
+Options:
+    --print-result      Print the value the script returns
+    --json              Print tokens and ast as JSON instead
+    --vm                Run scripts on the bytecode virtual machine
+    --optimize          Compute operations on literals before running scripts
+
+A script that returns a number exits with that number as its status,
+returning `false` exits with status 1. A returned string is written
+to stdout, any other value is only shown with --print-result.
+`lint` exits with status 1 when it reports any warnings.
+
+Scripts can read their arguments with `args()` (the count) and `args(i)`,
+environment variables with `env_var(name)` and stop with `exit(code)`.
+`help(f)` returns the signature and `///` doc comments of a function.
+When the script defines `function main(args)` it is called after the
+top level has run, unless the top level already returned a value.
+
+Running `alang-main <file> [args]` is the same as `alang-main run <file> [args]`.";
+
+#[derive(Debug, Default)]
+pub struct Options {
+    pub print_result: bool,
+    pub json: bool,
+    pub vm: bool,
+    pub optimize: bool,
+}
+
+#[derive(Debug)]
+pub enum Command {
+    Repl,
+    Help,
+    Run(String, Vec<String>),
+    Tokens(String),
+    Ast(String),
+    Check(String),
+    Eval(Option<String>),
+    Fmt {
+        files: Vec<String>,
+        check: bool,
+        width: Option<usize>,
+    },
+    Lint {
+        files: Vec<String>,
+        config: Option<String>,
+    },
+    Doc {
+        file: String,
+        html: bool,
+    },
+}
+
+#[derive(Debug)]
+pub struct UsageError {
+    pub message: String,
+}
+
+impl UsageError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for UsageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Parses the process arguments, without the executable name
+pub fn parse(args: &[String]) -> Result<(Command, Options), UsageError> {
+    let mut options = Options::default();
+    let mut args = args.iter().peekable();
+
+    parse_options(&mut args, &mut options)?;
+
+    let command = match args.next() {
+        Some(command) => command.as_str(),
+        None => return Ok((Command::Repl, options)),
+    };
+
+    if !matches!(command, "repl" | "help" | "fmt" | "lint" | "doc") {
+        parse_options(&mut args, &mut options)?;
+    }
+
+    let command = match command {
+        "repl" => Command::Repl,
+        "help" | "-h" | "--help" => Command::Help,
+        "run" => Command::Run(
+            file_argument(&mut args, command)?,
+            args.by_ref().cloned().collect(),
+        ),
+        "tokens" => Command::Tokens(file_argument(&mut args, command)?),
+        "ast" => Command::Ast(file_argument(&mut args, command)?),
+        "check" => Command::Check(file_argument(&mut args, command)?),
+        "eval" => match args.next().map(String::as_str) {
+            Some("-e") => match args.next() {
+                Some(source) => Command::Eval(Some(source.clone())),
+                None => return Err(UsageError::new("Missing code after -e")),
+            },
+            Some(argument) => {
+                return Err(UsageError::new(format!(
+                    "Unexpected argument '{}' for eval",
+                    argument
+                )))
+            }
+            None => Command::Eval(None),
+        },
+        "fmt" => {
+            let mut files = Vec::new();
+            let mut check = false;
+            let mut width = None;
+
+            while let Some(argument) = args.next() {
+                match argument.as_str() {
+                    "--check" => check = true,
+                    "--width" => match args.next().map(|width| width.parse()) {
+                        Some(Ok(value)) => width = Some(value),
+                        _ => return Err(UsageError::new("Expected a number after --width")),
+                    },
+                    file => files.push(file.to_string()),
+                }
+            }
+
+            if files.is_empty() {
+                return Err(UsageError::new("Missing files for 'fmt'"));
+            }
+
+            Command::Fmt {
+                files,
+                check,
+                width,
+            }
+        }
+        "lint" => {
+            let mut files = Vec::new();
+            let mut config = None;
+
+            while let Some(argument) = args.next() {
+                match argument.as_str() {
+                    "--config" => match args.next() {
+                        Some(file) => config = Some(file.clone()),
+                        None => return Err(UsageError::new("Missing file after --config")),
+                    },
+                    file => files.push(file.to_string()),
+                }
+            }
+
+            if files.is_empty() {
+                return Err(UsageError::new("Missing files for 'lint'"));
+            }
+
+            Command::Lint { files, config }
+        }
+        "doc" => {
+            let mut file = None;
+            let mut html = false;
+
+            for argument in args.by_ref() {
+                match argument.as_str() {
+                    "--html" => html = true,
+                    argument if file.is_none() => file = Some(argument.to_string()),
+                    argument => {
+                        return Err(UsageError::new(format!(
+                            "Unexpected argument '{}' for doc",
+                            argument
+                        )))
+                    }
+                }
+            }
+
+            match file {
+                Some(file) => Command::Doc { file, html },
+                None => return Err(UsageError::new("Missing file for 'doc'")),
+            }
+        }
+        // Anything else is a script to run
+        file => Command::Run(file.to_string(), args.by_ref().cloned().collect()),
+    };
+
+    if let Some(argument) = args.next() {
+        return Err(UsageError::new(format!(
+            "Unexpected argument '{}'",
+            argument
+        )));
+    }
+
+    Ok((command, options))
+}
+
+/// Consumes leading `--options`, stopping at the first other argument
+fn parse_options<'a>(
+    args: &mut Peekable<impl Iterator<Item = &'a String>>,
+    options: &mut Options,
+) -> Result<(), UsageError> {
+    while let Some(option) = args.next_if(|arg| arg.starts_with("--") && *arg != "--help") {
+        match option.as_str() {
+            "--print-result" => options.print_result = true,
+            "--json" => options.json = true,
+            "--vm" => options.vm = true,
+            "--optimize" => options.optimize = true,
+            option => return Err(UsageError::new(format!("Unknown option '{}'", option))),
+        }
+    }
+
+    Ok(())
+}
+
+fn file_argument<'a>(
+    args: &mut impl Iterator<Item = &'a String>,
+    command: &str,
+) -> Result<String, UsageError> {
+    match args.next() {
+        Some(file) => Ok(file.clone()),
+        None => Err(UsageError::new(format!("Missing file for '{}'", command))),
+    }
+}
@@ -1,35 +1,325 @@
+mod builtins;
+mod cli;
+mod repl;
+
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+};
+
 use alang_lib::{
-    interpreter::{self, Environment},
+    doc::{self, DocFormat},
+    errors::Error,
+    formatter::{self, FormatOptions},
+    interpreter,
+    json::ToJson,
     lexer::tokenize,
+    linter::{self, LintConfig},
+    optimizer,
     parser::Parser,
+    types::{RuntimeType, RuntimeValue},
+    vm,
 };
+use cli::{Command, Options};
+
+const EXIT_SUCCESS: i32 = 0;
+const EXIT_SCRIPT_ERROR: i32 = 1;
+const EXIT_USAGE_ERROR: i32 = 2;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let (command, options) = match cli::parse(&args) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            std::process::exit(EXIT_USAGE_ERROR);
+        }
+    };
+
+    let code = match command {
+        Command::Repl => match repl::Repl::new().run() {
+            Ok(()) => EXIT_SUCCESS,
+            Err(e) => {
+                eprintln!("{}", e);
+                EXIT_SCRIPT_ERROR
+            }
+        },
+        Command::Help => {
+            println!("{}", cli::USAGE);
+            EXIT_SUCCESS
+        }
+        Command::Run(file, args) => with_source(&file, |source| execute(source, args, &options)),
+        Command::Tokens(file) => with_source(&file, |source| {
+            let tokens = tokenize(source)?;
+            if options.json {
+                println!("{}", Vec::from(tokens).to_json().pretty());
+            } else {
+                println!("{:#?}", tokens);
+            }
+            Ok(EXIT_SUCCESS)
+        }),
+        Command::Ast(file) => with_source(&file, |source| {
+            let program = Parser::produce_ast(source)?;
+            if options.json {
+                println!("{}", program.to_json().pretty());
+            } else {
+                println!("{:#?}", program);
+            }
+            Ok(EXIT_SUCCESS)
+        }),
+        Command::Check(file) => with_source(&file, |source| {
+            let mut program = Parser::produce_ast(source)?;
+            interpreter::resolve(&mut program, &builtins::environment(Vec::new()))?;
+            Ok(EXIT_SUCCESS)
+        }),
+        Command::Eval(Some(source)) => report("<eval>", evaluate(&source, &options)),
+        Command::Fmt {
+            files,
+            check,
+            width,
+        } => {
+            let mut options = FormatOptions::default();
+            if let Some(width) = width {
+                options.line_width = width;
+            }
+
+            files
+                .iter()
+                .map(|file| with_source(file, |source| format(file, source, check, &options)))
+                .max()
+                .unwrap_or(EXIT_SUCCESS)
+        }
+        Command::Lint { files, config } => files
+            .iter()
+            .map(|file| {
+                let config = match lint_config(file, config.as_deref()) {
+                    Ok(config) => config,
+                    Err(code) => return code,
+                };
+
+                with_source(file, |source| lint(file, source, &config))
+            })
+            .max()
+            .unwrap_or(EXIT_SUCCESS),
+        Command::Doc { file, html } => with_source(&file, |source| {
+            let format = if html {
+                DocFormat::Html
+            } else {
+                DocFormat::Markdown
+            };
+
+            print!("{}", doc::generate(&file, source, format)?);
+            Ok(EXIT_SUCCESS)
+        }),
+        Command::Eval(None) => {
+            let mut source = String::new();
+            match std::io::stdin().read_to_string(&mut source) {
+                Ok(_) => report("<stdin>", evaluate(&source, &options)),
+                Err(e) => {
+                    eprintln!("Could not read stdin: {}", e);
+                    EXIT_USAGE_ERROR
+                }
+            }
+        }
+    };
+
+    std::process::exit(code);
+}
+
+fn execute(source: &str, args: Vec<String>, options: &Options) -> Result<i32, Error> {
+    let mut env = builtins::environment(args);
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args: Vec<String> = std::env::args().collect();
+    let mut program = Parser::produce_ast(source)?;
+    interpreter::resolve(&mut program, &env)?;
+    if options.optimize {
+        optimizer::optimize(&mut program);
+    }
 
-    let mut env = Environment::new();
+    let mut output = match options.vm {
+        true => vm::run(&program, &mut env)?,
+        false => interpreter::run(&program, &mut env)?,
+    };
 
-    if args.len() > 1 {
-        let filename = &args[1];
+    // Scripts may declare a `main` entry point, which gets called once the
+    // top level is done, given that it did not return a value already
+    if let RuntimeValue::Null = output {
+        let entry = match env.get("main") {
+            Some(RuntimeValue::Function(main)) => Some((
+                main.arity(),
+                main.declaration.name.line,
+                main.declaration.name.column,
+            )),
+            Some(RuntimeValue::Closure(main)) => {
+                Some((main.arity(), main.prototype.line, main.prototype.column))
+            }
+            _ => None,
+        };
 
-        // read the file
-        let source_code = std::fs::read_to_string(filename).expect("Could not read file");
+        if let Some((arity, line, column)) = entry {
+            let mut args = Vec::new();
+            if arity == 1 {
+                args.push(RuntimeType {
+                    value: env.get("args").unwrap_or(RuntimeValue::Null),
+                    line: 0,
+                    column: 0,
+                });
+            }
 
-        // parse the file
-        // let tokens = tokenize(&source_code)?;
+            let main = env.get("main").unwrap();
+            output = interpreter::call_function("main", &main, args, line, column)?.value;
+        }
+    }
 
-        let program = Parser::produce_ast(&source_code)?;
+    Ok(finish(&output, options))
+}
 
-        let output = interpreter::run(&program, &mut env)?;
+/// Like `execute`, but uses the value of the last expression when the
+/// code does not `return` anything. The value is printed like the REPL does
+/// and, unlike with `run`, does not decide the exit code.
+fn evaluate(source: &str, options: &Options) -> Result<i32, Error> {
+    let mut env = builtins::environment(Vec::new());
 
-        println!("{:#?}", output);
+    let mut program = Parser::produce_ast(source)?;
+    interpreter::resolve(&mut program, &env)?;
+    if options.optimize {
+        optimizer::optimize(&mut program);
+    }
 
-        std::process::exit(0);
+    let output = match options.vm {
+        true => vm::evaluate(&program, &mut env)?,
+        false => interpreter::evaluate(&program, &mut env)?,
+    };
+
+    if !matches!(output.value, RuntimeValue::Null) {
+        println!("{}", output.value);
     }
 
-    let tokens = tokenize("hello 2.15 + 102.50\n\"Some String\" rawr 12 '\n'")?;
+    Ok(EXIT_SUCCESS)
+}
 
-    println!("{:#?}", tokens);
+/// Formats the file in place, or with `check` only reports whether it needs formatting
+fn format(file: &str, source: &str, check: bool, options: &FormatOptions) -> Result<i32, Error> {
+    let formatted = formatter::format(source, options)?;
 
-    Ok(())
+    if formatted == source {
+        return Ok(EXIT_SUCCESS);
+    }
+
+    if check {
+        println!("{} is not formatted", file);
+        return Ok(EXIT_SCRIPT_ERROR);
+    }
+
+    if let Err(e) = std::fs::write(file, formatted) {
+        eprintln!("Could not write {}: {}", file, e);
+        return Ok(EXIT_USAGE_ERROR);
+    }
+
+    Ok(EXIT_SUCCESS)
+}
+
+/// Prints the warnings for the file, failing when there are any
+fn lint(file: &str, source: &str, config: &LintConfig) -> Result<i32, Error> {
+    let warnings = linter::lint(source, config)?;
+
+    for warning in &warnings {
+        println!(
+            "{}:{}:{}: warning: {} [{}]",
+            file,
+            warning.line,
+            warning.column,
+            warning.message,
+            warning.rule.name()
+        );
+    }
+
+    if warnings.is_empty() {
+        Ok(EXIT_SUCCESS)
+    } else {
+        Ok(EXIT_SCRIPT_ERROR)
+    }
+}
+
+/// Reads the given config, or the nearest `.alanglint` next to the file or in
+/// one of its parent directories, returning the exit code when it is invalid
+fn lint_config(file: &str, config: Option<&str>) -> Result<LintConfig, i32> {
+    let path = match config {
+        Some(config) => PathBuf::from(config),
+        None => {
+            let directory = Path::new(file).parent().unwrap_or(Path::new(""));
+            let directory = std::fs::canonicalize(directory).unwrap_or(directory.to_path_buf());
+
+            match directory
+                .ancestors()
+                .map(|directory| directory.join(".alanglint"))
+                .find(|path| path.is_file())
+            {
+                Some(path) => path,
+                None => return Ok(LintConfig::new()),
+            }
+        }
+    };
+
+    let source = std::fs::read_to_string(&path).map_err(|e| {
+        eprintln!("Could not read {}: {}", path.display(), e);
+        EXIT_USAGE_ERROR
+    })?;
+
+    LintConfig::parse(&source).map_err(|e| {
+        eprintln!("{}: {}", path.display(), e);
+        EXIT_USAGE_ERROR
+    })
+}
+
+/// Writes the script result to stdout and turns it into the exit code
+fn finish(result: &RuntimeValue, options: &Options) -> i32 {
+    match result {
+        RuntimeValue::Null => {}
+        RuntimeValue::String(_) => println!("{}", result),
+        _ if options.print_result => println!("{}", result),
+        _ => {}
+    }
+
+    match result {
+        RuntimeValue::Number(number) => exit_code(number.value()),
+        RuntimeValue::Boolean(boolean) if !boolean.value() => EXIT_SCRIPT_ERROR,
+        _ => EXIT_SUCCESS,
+    }
+}
+
+/// Turns a number returned by the script or passed to `exit` into the exit code.
+/// Only whole numbers from 0 to 255 are kept, anything else would be truncated or
+/// wrap around to a different code, possibly 0, so it fails the script instead.
+fn exit_code(code: f64) -> i32 {
+    if code.fract() == 0.0 && (0.0..=255.0).contains(&code) {
+        return code as i32;
+    }
+
+    eprintln!(
+        "Invalid exit code {}, expected a whole number from 0 to 255",
+        code
+    );
+    EXIT_SCRIPT_ERROR
+}
+
+/// Reads the file and hands its contents to `f`, returning the exit code
+fn with_source(file: &str, f: impl FnOnce(&str) -> Result<i32, Error>) -> i32 {
+    match std::fs::read_to_string(file) {
+        Ok(source) => report(file, f(&source)),
+        Err(e) => {
+            eprintln!("Could not read {}: {}", file, e);
+            EXIT_USAGE_ERROR
+        }
+    }
+}
+
+fn report(name: &str, result: Result<i32, Error>) -> i32 {
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("{}: {}", name, e);
+            EXIT_SCRIPT_ERROR
+        }
+    }
 }
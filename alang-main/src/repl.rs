@@ -0,0 +1,168 @@
+use std::io::{self, BufRead, Write};
+
+use crate::builtins;
+
+use alang_lib::{
+    errors::Error,
+    interpreter::{self, Environment},
+    lexer::tokenize,
+    parser::Parser,
+    types::RuntimeValue,
+};
+
+const HELP: &str = "\
+Commands:
+    :help           Show this message
+    :env            List the variables in the session
+    :tokens <code>  Show the tokens for <code>
+    :ast <code>     Show the syntax tree for <code>
+    :load <file>    Run a file inside the session
+    :quit           Leave the REPL
+
+Blocks (function/if ... end) can span multiple lines,
+enter an empty line to abandon an unfinished block.";
+
+pub struct Repl {
+    env: Environment,
+    buffer: String,
+}
+
+impl Repl {
+    pub fn new() -> Self {
+        Self {
+            env: builtins::environment(Vec::new()),
+            buffer: String::new(),
+        }
+    }
+
+    pub fn run(&mut self) -> io::Result<()> {
+        let stdin = io::stdin();
+        let mut input = stdin.lock();
+
+        println!("alang {} - type :help for help", env!("CARGO_PKG_VERSION"));
+
+        loop {
+            let prompt = if self.buffer.is_empty() { ">> " } else { ".. " };
+            print!("{}", prompt);
+            io::stdout().flush()?;
+
+            let mut line = String::new();
+            if input.read_line(&mut line)? == 0 {
+                // EOF (ctrl-d)
+                println!();
+                return Ok(());
+            }
+
+            let line = line.trim_end();
+
+            if self.buffer.is_empty() {
+                if let Some(command) = line.trim_start().strip_prefix(':') {
+                    if !self.command(command) {
+                        return Ok(());
+                    }
+                    continue;
+                }
+
+                if line.trim().is_empty() {
+                    continue;
+                }
+            } else if line.trim().is_empty() {
+                // Empty line while a block is open, evaluate what we have
+                // so the user gets to see what is wrong with it
+                let source = std::mem::take(&mut self.buffer);
+                self.execute(&source, false);
+                continue;
+            }
+
+            self.buffer.push_str(line);
+            self.buffer.push('\n');
+
+            let source = std::mem::take(&mut self.buffer);
+            if !self.execute(&source, true) {
+                self.buffer = source;
+            }
+        }
+    }
+
+    /// Handles a `:command`, returns false when the REPL should exit
+    fn command(&mut self, command: &str) -> bool {
+        let (name, argument) = match command.split_once(char::is_whitespace) {
+            Some((name, argument)) => (name, argument.trim()),
+            None => (command, ""),
+        };
+
+        match name {
+            "help" | "h" => println!("{}", HELP),
+            "quit" | "q" | "exit" => return false,
+            "env" => self.print_env(),
+            "tokens" => match tokenize(argument) {
+                Ok(tokens) => println!("{:#?}", tokens),
+                Err(e) => eprintln!("{}", e),
+            },
+            "ast" => match Parser::produce_ast(argument) {
+                Ok(program) => println!("{:#?}", program),
+                Err(e) => eprintln!("{}", e),
+            },
+            "load" => {
+                if argument.is_empty() {
+                    eprintln!("Usage: :load <file>");
+                    return true;
+                }
+
+                match std::fs::read_to_string(argument) {
+                    Ok(source) => {
+                        self.execute(&source, false);
+                    }
+                    Err(e) => eprintln!("Could not read {}: {}", argument, e),
+                }
+            }
+            _ => eprintln!("Unknown command :{}, type :help for help", name),
+        }
+
+        true
+    }
+
+    fn print_env(&self) {
+        let mut variables = self.env.iter().collect::<Vec<_>>();
+        variables.sort_by_key(|(name, _)| name.as_str());
+
+        for (name, value) in variables {
+            println!("{} = {}", name, value);
+        }
+    }
+
+    /// Parses and runs the source in the session environment.
+    ///
+    /// Returns false when `wait_for_more` is set and the source ended in the
+    /// middle of a block, meaning the caller should collect more lines first.
+    fn execute(&mut self, source: &str, wait_for_more: bool) -> bool {
+        let mut program = match Parser::produce_ast(source) {
+            Ok(program) => program,
+            Err(e) if wait_for_more && e.is_unexpected_eof() => return false,
+            Err(e) => {
+                report(&e);
+                return true;
+            }
+        };
+
+        if let Err(e) = interpreter::resolve(&mut program, &self.env) {
+            report(&e);
+            return true;
+        }
+
+        match interpreter::evaluate(&program, &mut self.env) {
+            Ok(result) => {
+                if !matches!(result.value, RuntimeValue::Null) {
+                    println!("{}", result);
+                }
+            }
+            Err(e) => report(&e),
+        }
+
+        true
+    }
+}
+
+fn report(error: &Error) {
+    eprintln!("{}", error);
+}